@@ -0,0 +1,257 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::{c_char, c_longlong};
+use jiff::civil::Date;
+use crate::date::TempusDate;
+use crate::holiday::TempusHolidayRules;
+use crate::utils::{roll_convention_from_i8, set_last_error_message, RollConvention};
+use crate::zoned::TempusZoned;
+
+/// Weekend days and holidays, for business-day arithmetic.
+#[repr(C)]
+#[derive(Clone)]
+pub struct TempusBusinessCalendar {
+    /// Indexed by the weekday's offset from Sunday (Sunday is 0).
+    weekend: [bool; 7],
+    holidays: BTreeSet<Date>,
+    rules: TempusHolidayRules,
+    /// The holidays computed from `rules`, by year.
+    rule_holidays: RefCell<BTreeMap<i16, BTreeSet<Date>>>,
+}
+
+impl TempusBusinessCalendar {
+    pub fn is_holiday(&self, date: Date) -> bool {
+        if self.holidays.contains(&date) {
+            return true
+        }
+        if self.rules.rules.is_empty() {
+            return false
+        }
+        self.rule_holidays.borrow_mut()
+            .entry(date.year())
+            .or_insert_with(|| self.rules.holidays(date.year()).into_iter().map(|(holiday, _)| holiday).collect())
+            .contains(&date)
+    }
+
+    pub fn is_business_day(&self, date: Date) -> bool {
+        !self.weekend[date.weekday().to_sunday_zero_offset() as usize] && !self.is_holiday(date)
+    }
+
+    /// The nearest business day strictly after (or before) the given date.
+    pub fn next_business_day(&self, date: Date, forward: bool) -> Result<Date, String> {
+        let mut date = date;
+        loop {
+            date = if forward { date.tomorrow() } else { date.yesterday() }.map_err(|e| e.to_string())?;
+            if self.is_business_day(date) {
+                return Ok(date)
+            }
+        }
+    }
+
+    /// Moves the date by the given number of business days. Starting from a day that isn't a business day, the
+    /// first step lands on the nearest business day.
+    pub fn add_business_days(&self, date: Date, days: i64) -> Result<Date, String> {
+        let mut date = date;
+        for _ in 0..days.unsigned_abs() {
+            date = self.next_business_day(date, days > 0)?;
+        }
+        Ok(date)
+    }
+
+    /// The number of business days from `start` (inclusive) to `end` (exclusive). Negative if `end` is before `start`.
+    pub fn business_days_between(&self, start: Date, end: Date) -> Result<i64, String> {
+        let (from, to, sign) = if start <= end { (start, end, 1) } else { (end, start, -1) };
+        let mut count = 0;
+        let mut date = from;
+        while date < to {
+            if self.is_business_day(date) {
+                count += 1;
+            }
+            date = date.tomorrow().map_err(|e| e.to_string())?;
+        }
+        Ok(count * sign)
+    }
+
+    /// Moves a date that isn't a business day onto one. The modified conventions go the other way instead when the
+    /// date would otherwise move into a different month.
+    pub fn roll(&self, date: Date, convention: RollConvention) -> Result<Date, String> {
+        if self.is_business_day(date) {
+            return Ok(date)
+        }
+        match convention {
+            RollConvention::Following => self.next_business_day(date, true),
+            RollConvention::Preceding => self.next_business_day(date, false),
+            RollConvention::ModifiedFollowing => {
+                let rolled = self.next_business_day(date, true)?;
+                if rolled.month() == date.month() {
+                    Ok(rolled)
+                } else {
+                    self.next_business_day(date, false)
+                }
+            }
+            RollConvention::ModifiedPreceding => {
+                let rolled = self.next_business_day(date, false)?;
+                if rolled.month() == date.month() {
+                    Ok(rolled)
+                } else {
+                    self.next_business_day(date, true)
+                }
+            }
+        }
+    }
+}
+
+/// `weekend_mask` has a bit set for each weekend day, by the weekday's offset from Sunday (bit 0 is Sunday).
+#[no_mangle]
+pub extern "C" fn business_calendar_new(weekend_mask: i8, out_calendar: *mut *mut TempusBusinessCalendar) -> c_longlong {
+    if weekend_mask & 0x7f == 0x7f {
+        set_last_error_message("at least one day of the week must not be a weekend day".to_string());
+        return -1
+    }
+    let mut weekend = [false; 7];
+    for (offset, weekend_day) in weekend.iter_mut().enumerate() {
+        *weekend_day = weekend_mask & (1 << offset) != 0;
+    }
+    let handle = Box::new(TempusBusinessCalendar{weekend, holidays: BTreeSet::new(), rules: TempusHolidayRules::default(), rule_holidays: RefCell::new(BTreeMap::new())});
+    unsafe {
+        *out_calendar = Box::into_raw(handle);
+    }
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn business_calendar_add_holiday(tcal: &mut TempusBusinessCalendar, tdate: &TempusDate) -> c_longlong {
+    tcal.holidays.insert(tdate.date);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn business_calendar_remove_holiday(tcal: &mut TempusBusinessCalendar, tdate: &TempusDate) -> c_longlong {
+    tcal.holidays.remove(&tdate.date);
+    0
+}
+
+/// Adds the holidays described by a set of holiday rules, in every year.
+#[no_mangle]
+pub extern "C" fn business_calendar_add_holiday_rules(tcal: &mut TempusBusinessCalendar, trules: &TempusHolidayRules) -> c_longlong {
+    tcal.rules.rules.extend(trules.rules.iter().cloned());
+    tcal.rule_holidays.borrow_mut().clear();
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn business_calendar_is_holiday(tcal: &TempusBusinessCalendar, tdate: &TempusDate) -> c_char {
+    tcal.is_holiday(tdate.date) as i8
+}
+
+#[no_mangle]
+pub extern "C" fn business_calendar_is_business_day(tcal: &TempusBusinessCalendar, tdate: &TempusDate) -> c_char {
+    tcal.is_business_day(tdate.date) as i8
+}
+
+#[no_mangle]
+pub extern "C" fn business_calendar_add_business_days(tcal: &TempusBusinessCalendar, tdate: &TempusDate, days: i64, out_date: *mut *mut TempusDate) -> c_longlong {
+    match tcal.add_business_days(tdate.date, days) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            let new_date = TempusDate{date};
+            new_date.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+/// Like `business_calendar_add_business_days`, keeping the zoned datetime's time of day and time zone.
+#[no_mangle]
+pub extern "C" fn business_calendar_add_business_days_zoned(tcal: &TempusBusinessCalendar, tzoned: &TempusZoned, days: i64, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    let date = match tcal.add_business_days(tzoned.zoned.date(), days) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(date) => date
+    };
+    match tzoned.zoned.with().date(date).build() {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(zoned) => {
+            let new_zoned = TempusZoned{zoned};
+            new_zoned.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn business_calendar_business_days_between(tcal: &TempusBusinessCalendar, start: &TempusDate, end: &TempusDate, out_days: &mut i64) -> c_longlong {
+    match tcal.business_days_between(start.date, end.date) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(days) => {
+            *out_days = days;
+            0
+        }
+    }
+}
+
+/// The nearest business day after (`forward_i` = 1) or before (`forward_i` = 0) the given date.
+#[no_mangle]
+pub extern "C" fn business_calendar_next_business_day(tcal: &TempusBusinessCalendar, tdate: &TempusDate, forward_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    let forward = match forward_i {
+        0 => false,
+        1 => true,
+        _ => {
+            set_last_error_message("invalid options".to_string());
+            return -1
+        }
+    };
+    match tcal.next_business_day(tdate.date, forward) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(date) => {
+            let new_date = TempusDate{date};
+            new_date.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn business_calendar_roll(tcal: &TempusBusinessCalendar, tdate: &TempusDate, convention_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    let convention = match roll_convention_from_i8(convention_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(convention) => convention
+    };
+    match tcal.roll(tdate.date, convention) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(date) => {
+            let new_date = TempusDate{date};
+            new_date.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn free_business_calendar(tcal: Box<TempusBusinessCalendar>) -> c_longlong {
+    let raw = Box::into_raw(tcal);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
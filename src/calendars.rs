@@ -0,0 +1,517 @@
+use std::ffi::{c_char, c_int, c_longlong};
+use std::fmt::{Display, Formatter};
+use jiff::civil::Date;
+use crate::date::{date_of_day_count, day_count_of, TempusDate, RATA_DIE_UNIX_EPOCH};
+use crate::utils::{calendar_from_i8, set_last_error_message, string_into_ahk_buff, AHKStringBuffer, Calendar};
+
+// The fixed days (Rata Die, where 0001-01-01 Gregorian is day 1) that each calendar's arithmetic counts from
+const JULIAN_EPOCH: i64 = -1;
+const PERSIAN_EPOCH: i64 = 226_895;
+const ISLAMIC_EPOCH: i64 = 227_015;
+const HEBREW_EPOCH: i64 = -1_373_427;
+
+const JULIAN_MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November",
+    "December",
+];
+const PERSIAN_MONTHS: [&str; 12] = [
+    "Farvardin", "Ordibehesht", "Khordad", "Tir", "Mordad", "Shahrivar", "Mehr", "Aban", "Azar", "Dey", "Bahman",
+    "Esfand",
+];
+const ISLAMIC_MONTHS: [&str; 12] = [
+    "Muharram", "Safar", "Rabi al-Awwal", "Rabi al-Thani", "Jumada al-Awwal", "Jumada al-Thani", "Rajab", "Shaban",
+    "Ramadan", "Shawwal", "Dhu al-Qadah", "Dhu al-Hijjah",
+];
+
+/// The Hebrew months, in the order they fall in the year from Tishrei. A common year has `Adar`, a leap year has
+/// `AdarI` and `AdarII` in its place.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HebrewMonth {
+    Tishrei, Cheshvan, Kislev, Tevet, Shevat, Adar, AdarI, AdarII, Nisan, Iyar, Sivan, Tammuz, Av, Elul,
+}
+
+const HEBREW_COMMON_YEAR: [HebrewMonth; 12] = [
+    HebrewMonth::Tishrei, HebrewMonth::Cheshvan, HebrewMonth::Kislev, HebrewMonth::Tevet, HebrewMonth::Shevat,
+    HebrewMonth::Adar, HebrewMonth::Nisan, HebrewMonth::Iyar, HebrewMonth::Sivan, HebrewMonth::Tammuz, HebrewMonth::Av,
+    HebrewMonth::Elul,
+];
+const HEBREW_LEAP_YEAR: [HebrewMonth; 13] = [
+    HebrewMonth::Tishrei, HebrewMonth::Cheshvan, HebrewMonth::Kislev, HebrewMonth::Tevet, HebrewMonth::Shevat,
+    HebrewMonth::AdarI, HebrewMonth::AdarII, HebrewMonth::Nisan, HebrewMonth::Iyar, HebrewMonth::Sivan,
+    HebrewMonth::Tammuz, HebrewMonth::Av, HebrewMonth::Elul,
+];
+
+impl HebrewMonth {
+    fn name(self) -> &'static str {
+        match self {
+            HebrewMonth::Tishrei => "Tishrei",
+            HebrewMonth::Cheshvan => "Cheshvan",
+            HebrewMonth::Kislev => "Kislev",
+            HebrewMonth::Tevet => "Tevet",
+            HebrewMonth::Shevat => "Shevat",
+            HebrewMonth::Adar => "Adar",
+            HebrewMonth::AdarI => "Adar I",
+            HebrewMonth::AdarII => "Adar II",
+            HebrewMonth::Nisan => "Nisan",
+            HebrewMonth::Iyar => "Iyar",
+            HebrewMonth::Sivan => "Sivan",
+            HebrewMonth::Tammuz => "Tammuz",
+            HebrewMonth::Av => "Av",
+            HebrewMonth::Elul => "Elul",
+        }
+    }
+
+    fn days(self, year: i32) -> i8 {
+        let days_in_year = hebrew_new_year(year + 1) - hebrew_new_year(year);
+        match self {
+            HebrewMonth::Cheshvan if days_in_year % 10 == 5 => 30,
+            HebrewMonth::Kislev if days_in_year % 10 == 3 => 29,
+            HebrewMonth::Tishrei | HebrewMonth::Kislev | HebrewMonth::Shevat | HebrewMonth::AdarI | HebrewMonth::Nisan
+            | HebrewMonth::Sivan | HebrewMonth::Av => 30,
+            _ => 29,
+        }
+    }
+}
+
+fn hebrew_is_leap(year: i32) -> bool {
+    (7 * year as i64 + 1).rem_euclid(19) < 7
+}
+
+fn hebrew_months(year: i32) -> &'static [HebrewMonth] {
+    if hebrew_is_leap(year) { &HEBREW_LEAP_YEAR } else { &HEBREW_COMMON_YEAR }
+}
+
+/// Days from the epoch to the molad of Tishrei of the year, postponed when it falls on a Sunday, Wednesday or Friday.
+fn hebrew_elapsed_days(year: i32) -> i64 {
+    let months_elapsed = (235 * year as i64 - 234).div_euclid(19);
+    let parts_elapsed = 12084 + 13753 * months_elapsed;
+    let days = 29 * months_elapsed + parts_elapsed.div_euclid(25920);
+    if (3 * (days + 1)).rem_euclid(7) < 3 { days + 1 } else { days }
+}
+
+/// The fixed day of 1 Tishrei of the year, after the postponements that keep years 353-355 or 383-385 days long.
+fn hebrew_new_year(year: i32) -> i64 {
+    let (previous, this, next) = (hebrew_elapsed_days(year - 1), hebrew_elapsed_days(year), hebrew_elapsed_days(year + 1));
+    let correction = if next - this == 356 { 2 } else if this - previous == 382 { 1 } else { 0 };
+    HEBREW_EPOCH + this + correction
+}
+
+fn julian_is_leap(year: i32) -> bool {
+    year.rem_euclid(4) == 0
+}
+
+fn persian_is_leap(year: i32) -> bool {
+    (25 * year as i64 + 11).rem_euclid(33) < 8
+}
+
+fn islamic_is_leap(year: i32) -> bool {
+    (14 + 11 * year as i64).rem_euclid(30) < 11
+}
+
+/// Days from the start of a Persian year to the first of the month.
+fn persian_days_before_month(month: i8) -> i64 {
+    let month = month as i64 - 1;
+    if month < 6 { 31 * month } else { 30 * month + 6 }
+}
+
+/// A date in one of the supported calendars.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalendarDate {
+    calendar: Calendar,
+    year: i32,
+    month: i8,
+    day: i8,
+}
+
+/// Checks that some of the year falls within the dates jiff supports, which also keeps the year arithmetic of the
+/// calendars from overflowing.
+fn check_year(calendar: Calendar, year: i32) -> Result<(), String> {
+    let (first, last) = (CalendarDate::from_date(calendar, Date::MIN).year, CalendarDate::from_date(calendar, Date::MAX).year);
+    if !(first..=last).contains(&year) {
+        return Err(format!("year {year} is out of range for the {calendar:?} calendar ({first}..={last})"))
+    }
+    Ok(())
+}
+
+impl CalendarDate {
+    pub fn new(calendar: Calendar, year: i32, month: i8, day: i8) -> Result<Self, String> {
+        check_year(calendar, year)?;
+        if !(1..=months_in_year(calendar, year)).contains(&month) {
+            return Err(format!("month {month} is out of range for year {year} of the {calendar:?} calendar"))
+        }
+        if !(1..=days_in_month(calendar, year, month)).contains(&day) {
+            return Err(format!("day {day} is out of range for month {month} of year {year} of the {calendar:?} calendar"))
+        }
+        let date = CalendarDate{calendar, year, month, day};
+        date.to_date()?;
+        Ok(date)
+    }
+
+    pub fn from_date(calendar: Calendar, date: Date) -> Self {
+        let fixed = day_count_of(date, RATA_DIE_UNIX_EPOCH);
+        let year = match calendar {
+            Calendar::Julian => (4 * (fixed - JULIAN_EPOCH) + 1464).div_euclid(1461) as i32,
+            Calendar::Persian => (1 + (33 * (fixed - PERSIAN_EPOCH) + 3).div_euclid(12053)) as i32,
+            Calendar::Islamic => (30 * (fixed - ISLAMIC_EPOCH) + 10646).div_euclid(10631) as i32,
+            Calendar::Hebrew => {
+                // dividing by the mean year of 35975351/98496 days never overshoots, and undershoots by at most a year
+                let mut year = (98496 * (fixed - HEBREW_EPOCH)).div_euclid(35975351) as i32;
+                while hebrew_new_year(year + 1) <= fixed {
+                    year += 1;
+                }
+                year
+            }
+        };
+        let first_of = |month: i8| CalendarDate{calendar, year, month, day: 1}.fixed();
+        let month = (1..=months_in_year(calendar, year)).rev().find(|&month| first_of(month) <= fixed).unwrap_or(1);
+        let day = (fixed - first_of(month) + 1) as i8;
+        CalendarDate{calendar, year, month, day}
+    }
+
+    /// The Rata Die of the date.
+    fn fixed(self) -> i64 {
+        let (year, month, day) = (self.year as i64, self.month as i64, self.day as i64);
+        match self.calendar {
+            Calendar::Julian => {
+                let correction = if month <= 2 { 0 } else if julian_is_leap(self.year) { -1 } else { -2 };
+                JULIAN_EPOCH - 1 + 365 * (year - 1) + (year - 1).div_euclid(4) + (367 * month - 362).div_euclid(12) + correction + day
+            }
+            Calendar::Persian => {
+                PERSIAN_EPOCH - 1 + 365 * (year - 1) + (8 * year + 21).div_euclid(33) + persian_days_before_month(self.month) + day
+            }
+            Calendar::Islamic => {
+                ISLAMIC_EPOCH - 1 + 354 * (year - 1) + (3 + 11 * year).div_euclid(30) + 29 * (month - 1) + month / 2 + day
+            }
+            Calendar::Hebrew => {
+                let months_before = &hebrew_months(self.year)[..self.month as usize - 1];
+                hebrew_new_year(self.year) + months_before.iter().map(|m| m.days(self.year) as i64).sum::<i64>() + day - 1
+            }
+        }
+    }
+
+    /// The Gregorian date. Errors if it's outside the range jiff supports.
+    pub fn to_date(self) -> Result<Date, String> {
+        date_of_day_count(self.fixed(), RATA_DIE_UNIX_EPOCH)
+    }
+
+    pub fn month_name(self) -> &'static str {
+        match self.calendar {
+            Calendar::Julian => JULIAN_MONTHS[self.month as usize - 1],
+            Calendar::Persian => PERSIAN_MONTHS[self.month as usize - 1],
+            Calendar::Islamic => ISLAMIC_MONTHS[self.month as usize - 1],
+            Calendar::Hebrew => hebrew_months(self.year)[self.month as usize - 1].name(),
+        }
+    }
+
+    pub fn checked_add_days(self, days: i64) -> Result<Self, String> {
+        let date = self.fixed().checked_add(days).ok_or("day arithmetic overflowed".to_string()).and_then(|fixed| date_of_day_count(fixed, RATA_DIE_UNIX_EPOCH))?;
+        Ok(CalendarDate::from_date(self.calendar, date))
+    }
+
+    /// Moves the date by whole months, keeping the day of the month unless the new month is shorter.
+    pub fn checked_add_months(self, months: i64) -> Result<Self, String> {
+        let (year, month) = match self.calendar {
+            Calendar::Hebrew => {
+                // 19 years of the Hebrew calendar always have 235 months
+                let cycles = months.div_euclid(235);
+                let mut year = i32::try_from(cycles * 19 + self.year as i64).map_err(|_| "month arithmetic overflowed")?;
+                check_year(self.calendar, year)?;
+                let mut month = self.month as i64 + months.rem_euclid(235);
+                while month > months_in_year(Calendar::Hebrew, year) as i64 {
+                    month -= months_in_year(Calendar::Hebrew, year) as i64;
+                    year += 1;
+                }
+                (year, month as i8)
+            }
+            _ => {
+                let total = self.month as i64 - 1 + months;
+                let year = i32::try_from(self.year as i64 + total.div_euclid(12)).map_err(|_| "month arithmetic overflowed")?;
+                check_year(self.calendar, year)?;
+                (year, (total.rem_euclid(12) + 1) as i8)
+            }
+        };
+        let day = self.day.min(days_in_month(self.calendar, year, month));
+        CalendarDate::new(self.calendar, year, month, day)
+    }
+
+    /// Moves the date by whole years, keeping the month and day unless the day doesn't exist in the new year. In the
+    /// Hebrew calendar, Adar I and Adar II become Adar in a common year, and Adar becomes Adar II in a leap year.
+    pub fn checked_add_years(self, years: i64) -> Result<Self, String> {
+        let year = i32::try_from(self.year as i64 + years).map_err(|_| "year arithmetic overflowed")?;
+        check_year(self.calendar, year)?;
+        let month = match self.calendar {
+            Calendar::Hebrew => match (hebrew_is_leap(self.year), hebrew_is_leap(year)) {
+                (true, false) if self.month >= 7 => self.month - 1,
+                (false, true) if self.month >= 6 => self.month + 1,
+                _ => self.month,
+            }
+            _ => self.month,
+        };
+        let day = self.day.min(days_in_month(self.calendar, year, month));
+        CalendarDate::new(self.calendar, year, month, day)
+    }
+}
+
+/// Writes the date as its day, month name and year, like "10 Ramadan 1446".
+impl Display for CalendarDate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.day, self.month_name(), self.year)
+    }
+}
+
+pub fn months_in_year(calendar: Calendar, year: i32) -> i8 {
+    match calendar {
+        Calendar::Hebrew => hebrew_months(year).len() as i8,
+        _ => 12,
+    }
+}
+
+pub fn days_in_month(calendar: Calendar, year: i32, month: i8) -> i8 {
+    match calendar {
+        Calendar::Julian => match month {
+            2 if julian_is_leap(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+        Calendar::Persian => match month {
+            1..=6 => 31,
+            12 if !persian_is_leap(year) => 29,
+            _ => 30,
+        }
+        Calendar::Islamic => match month {
+            12 if islamic_is_leap(year) => 30,
+            _ if month % 2 == 1 => 30,
+            _ => 29,
+        }
+        Calendar::Hebrew => hebrew_months(year)[month as usize - 1].days(year),
+    }
+}
+
+#[repr(C)]
+pub struct TempusCalendarDate {
+    pub date: CalendarDate,
+}
+
+impl TempusCalendarDate {
+    pub fn stuff_into(self, pointer: *mut *mut TempusCalendarDate) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_new(calendar_i: i8, year: i32, month: i8, day: i8, out_date: *mut *mut TempusCalendarDate) -> c_longlong {
+    match calendar_from_i8(calendar_i).and_then(|calendar| CalendarDate::new(calendar, year, month, day)) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            TempusCalendarDate{date}.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+/// The Gregorian date in the given calendar.
+#[no_mangle]
+pub extern "C" fn calendar_date_from_date(td: &TempusDate, calendar_i: i8, out_date: *mut *mut TempusCalendarDate) -> c_longlong {
+    match calendar_from_i8(calendar_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(calendar) => {
+            TempusCalendarDate{date: CalendarDate::from_date(calendar, td.date)}.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_to_date(tcd: &TempusCalendarDate) -> Box<TempusDate> {
+    // every CalendarDate is made from, or checked against, a Gregorian date in range
+    Box::new(TempusDate{date: tcd.date.to_date().unwrap()})
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_calendar(tcd: &TempusCalendarDate) -> c_char {
+    match tcd.date.calendar {
+        Calendar::Julian => 1,
+        Calendar::Persian => 2,
+        Calendar::Islamic => 3,
+        Calendar::Hebrew => 4,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_year(tcd: &TempusCalendarDate) -> c_int {
+    tcd.date.year
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_month(tcd: &TempusCalendarDate) -> c_char {
+    tcd.date.month
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_day(tcd: &TempusCalendarDate) -> c_char {
+    tcd.date.day
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_months_in_year(tcd: &TempusCalendarDate) -> c_char {
+    months_in_year(tcd.date.calendar, tcd.date.year)
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_days_in_month(tcd: &TempusCalendarDate) -> c_char {
+    days_in_month(tcd.date.calendar, tcd.date.year, tcd.date.month)
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_month_name_length(tcd: &TempusCalendarDate) -> usize {
+    tcd.date.month_name().len()
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_month_name(tcd: &TempusCalendarDate, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(tcd.date.month_name().to_string(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_add_days(tcd: &TempusCalendarDate, days: i64, out_date: *mut *mut TempusCalendarDate) -> c_longlong {
+    match tcd.date.checked_add_days(days) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            TempusCalendarDate{date}.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_add_months(tcd: &TempusCalendarDate, months: i64, out_date: *mut *mut TempusCalendarDate) -> c_longlong {
+    match tcd.date.checked_add_months(months) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            TempusCalendarDate{date}.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_add_years(tcd: &TempusCalendarDate, years: i64, out_date: *mut *mut TempusCalendarDate) -> c_longlong {
+    match tcd.date.checked_add_years(years) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            TempusCalendarDate{date}.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_string_length(tcd: &TempusCalendarDate) -> usize {
+    tcd.date.to_string().len()
+}
+
+#[no_mangle]
+pub extern "C" fn calendar_date_to_string(tcd: &TempusCalendarDate, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(tcd.date.to_string(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_calendar_date(tcd: Box<TempusCalendarDate>) -> c_longlong {
+    let raw = Box::into_raw(tcd);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn in_calendar(calendar: Calendar, date: &str) -> String {
+        CalendarDate::from_date(calendar, date.parse().unwrap()).to_string()
+    }
+
+    #[test]
+    fn test_known_dates() {
+        assert_eq!(in_calendar(Calendar::Julian, "2025-03-10"), "25 February 2025");
+        assert_eq!(in_calendar(Calendar::Julian, "1582-10-15"), "5 October 1582");
+        assert_eq!(in_calendar(Calendar::Persian, "2025-03-21"), "1 Farvardin 1404");
+        assert_eq!(in_calendar(Calendar::Persian, "2025-03-20"), "30 Esfand 1403");
+        assert_eq!(in_calendar(Calendar::Islamic, "2025-03-01"), "1 Ramadan 1446");
+        assert_eq!(in_calendar(Calendar::Islamic, "0622-07-19"), "1 Muharram 1");
+        assert_eq!(in_calendar(Calendar::Hebrew, "2024-10-03"), "1 Tishrei 5785");
+        assert_eq!(in_calendar(Calendar::Hebrew, "2025-09-23"), "1 Tishrei 5786");
+        assert_eq!(in_calendar(Calendar::Hebrew, "2024-03-24"), "14 Adar II 5784");
+        assert_eq!(in_calendar(Calendar::Hebrew, "2025-03-14"), "14 Adar 5785");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for calendar in [Calendar::Julian, Calendar::Persian, Calendar::Islamic, Calendar::Hebrew] {
+            let mut date: Date = "1899-12-25".parse().unwrap();
+            let mut previous = CalendarDate::from_date(calendar, date.yesterday().unwrap());
+            for _ in 0..40_000 {
+                let calendar_date = CalendarDate::from_date(calendar, date);
+                assert_eq!(calendar_date.to_date().unwrap(), date);
+                assert_eq!(CalendarDate::new(calendar, calendar_date.year, calendar_date.month, calendar_date.day), Ok(calendar_date));
+                assert_eq!(previous.checked_add_days(1), Ok(calendar_date));
+                previous = calendar_date;
+                date = date.tomorrow().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_month_and_year_arithmetic() {
+        let date = CalendarDate::new(Calendar::Hebrew, 5784, 6, 30).unwrap();
+        assert_eq!(date.to_string(), "30 Adar I 5784");
+        assert_eq!(date.checked_add_years(1).unwrap().to_string(), "29 Adar 5785");
+        assert_eq!(date.checked_add_months(1).unwrap().to_string(), "29 Adar II 5784");
+        assert_eq!(date.checked_add_months(235).unwrap().to_string(), "30 Adar I 5803");
+        assert_eq!(date.checked_add_months(-6).unwrap().to_string(), "29 Elul 5783");
+        let date = CalendarDate::new(Calendar::Persian, 1403, 12, 30).unwrap();
+        assert_eq!(date.checked_add_years(1).unwrap().to_string(), "29 Esfand 1404");
+        assert_eq!(date.checked_add_months(1).unwrap().to_string(), "30 Farvardin 1404");
+        assert!(CalendarDate::new(Calendar::Islamic, 1446, 9, 30).is_ok());
+        assert!(CalendarDate::new(Calendar::Islamic, 1446, 2, 30).is_err());
+        assert!(CalendarDate::new(Calendar::Hebrew, 5785, 13, 1).is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_years() {
+        for calendar in [Calendar::Julian, Calendar::Persian, Calendar::Islamic, Calendar::Hebrew] {
+            assert!(CalendarDate::new(calendar, i32::MAX, 1, 1).is_err());
+            assert!(CalendarDate::new(calendar, i32::MIN, 1, 1).is_err());
+            let first = CalendarDate::from_date(calendar, Date::MIN);
+            let last = CalendarDate::from_date(calendar, Date::MAX);
+            assert!(first.checked_add_days(-1).is_err());
+            assert!(last.checked_add_days(1).is_err());
+            assert!(last.checked_add_days(1_000_000_000_000_000).is_err());
+            assert!(last.checked_add_years(i32::MAX as i64 - 1).is_err());
+            assert!(last.checked_add_months(i64::MAX / 20).is_err());
+            assert!(first.checked_add_months(-(i64::MAX / 20)).is_err());
+        }
+    }
+}
@@ -0,0 +1,548 @@
+use std::ffi::c_longlong;
+use std::str::FromStr;
+use jiff::civil::{Date, DateTime, Time, Weekday};
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Timestamp, ToSpan};
+use crate::utils::{ahk_str_to_string, set_last_error_message, string_into_ahk_buff, AHKStringBuffer, AHKWstr};
+use crate::zoned::TempusZoned;
+
+const MONTH_NAMES: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+const MACROS: [(&str, &str); 7] = [
+    ("@yearly", "0 0 1 1 *"), ("@annually", "0 0 1 1 *"), ("@monthly", "0 0 1 * *"), ("@weekly", "0 0 * * 0"),
+    ("@daily", "0 0 * * *"), ("@midnight", "0 0 * * *"), ("@hourly", "0 * * * *"),
+];
+
+/// How far to look for a fire time before giving up.
+const MAX_SEARCH_YEARS: i32 = 400;
+
+fn parse_value(s: &str, min: i8, max: i8, names: &[&str], first_name: i8, what: &str) -> Result<i8, String> {
+    if let Some(index) = names.iter().position(|name| name.eq_ignore_ascii_case(s)) {
+        return Ok(first_name + index as i8)
+    }
+    let value: i8 = s.parse().map_err(|_| format!("invalid {what} '{s}'"))?;
+    if value < min || value > max {
+        return Err(format!("{what} {value} is out of range ({min}-{max})"))
+    }
+    Ok(value)
+}
+
+/// Parses a list of values, ranges and steps (like `1,15-20,*/10`) into a bit mask.
+fn parse_item(item: &str, min: i8, max: i8, names: &[&str], first_name: i8, what: &str, allow_any: bool) -> Result<u64, String> {
+    let (range, step) = match item.split_once('/') {
+        None => (item, None),
+        Some((range, step)) => {
+            let step: u8 = step.parse().map_err(|_| format!("invalid {what} step '{step}'"))?;
+            if step == 0 {
+                return Err(format!("{what} step must not be 0"))
+            }
+            (range, Some(step as usize))
+        }
+    };
+    let (start, end) = if range == "*" || (allow_any && range == "?") {
+        (min, max)
+    } else if let Some((start, end)) = range.split_once('-') {
+        (parse_value(start, min, max, names, first_name, what)?, parse_value(end, min, max, names, first_name, what)?)
+    } else {
+        let start = parse_value(range, min, max, names, first_name, what)?;
+        // `5/15` means every 15 starting from 5
+        (start, if step.is_some() { max } else { start })
+    };
+    if start > end {
+        return Err(format!("{what} range '{range}' ends before it starts"))
+    }
+    let mut mask = 0;
+    for value in (start..=end).step_by(step.unwrap_or(1)) {
+        mask |= 1 << value;
+    }
+    Ok(mask)
+}
+
+fn parse_field(field: &str, min: i8, max: i8, names: &[&str], first_name: i8, what: &str) -> Result<u64, String> {
+    let mut mask = 0;
+    for item in field.split(',') {
+        mask |= parse_item(item, min, max, names, first_name, what, false)?;
+    }
+    Ok(mask)
+}
+
+fn is_set(mask: u64, value: i8) -> bool {
+    mask & (1 << value) != 0
+}
+
+/// The smallest value in the mask that is at least `from`.
+fn next_set(mask: u64, from: i8) -> Option<i8> {
+    if from > 63 {
+        return None
+    }
+    let masked = mask & (u64::MAX << from);
+    (masked != 0).then(|| masked.trailing_zeros() as i8)
+}
+
+/// The largest value in the mask that is at most `upto`.
+fn previous_set(mask: u64, upto: i8) -> Option<i8> {
+    if upto < 0 {
+        return None
+    }
+    let masked = mask & (u64::MAX >> (63 - upto));
+    (masked != 0).then(|| 63 - masked.leading_zeros() as i8)
+}
+
+fn last_working_day_of_month(date: Date) -> i8 {
+    let last = date.last_of_month();
+    match last.weekday() {
+        Weekday::Saturday => last.day() - 1,
+        Weekday::Sunday => last.day() - 2,
+        _ => last.day(),
+    }
+}
+
+/// The weekday (Monday through Friday) nearest to the given day of the month, without leaving the month. `None` if
+/// the month is too short to have the day.
+fn nearest_working_day(date: Date, day: i8) -> Option<i8> {
+    let days_in_month = date.days_in_month();
+    if day > days_in_month {
+        return None
+    }
+    let target = date.first_of_month().checked_add((day as i32 - 1).days()).ok()?;
+    Some(match target.weekday() {
+        Weekday::Saturday if day == 1 => day + 2,
+        Weekday::Saturday => day - 1,
+        Weekday::Sunday if day == days_in_month => day - 2,
+        Weekday::Sunday => day + 1,
+        _ => day,
+    })
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct DayOfMonth {
+    days: u64,
+    /// `L` is 0 days before the end of the month, `L-3` is 3.
+    before_last: Vec<i8>,
+    /// `LW`
+    last_working_day: bool,
+    /// `15W`
+    nearest_working_days: Vec<i8>,
+}
+
+impl DayOfMonth {
+    fn parse(field: &str) -> Result<Self, String> {
+        let mut ret = DayOfMonth::default();
+        for item in field.split(',') {
+            let upper = item.to_ascii_uppercase();
+            if upper == "L" {
+                ret.before_last.push(0);
+            } else if upper == "LW" {
+                ret.last_working_day = true;
+            } else if let Some(offset) = upper.strip_prefix("L-") {
+                ret.before_last.push(parse_value(offset, 0, 30, &[], 0, "day of month offset")?);
+            } else if let Some(day) = upper.strip_suffix('W') {
+                ret.nearest_working_days.push(parse_value(day, 1, 31, &[], 0, "day of month")?);
+            } else {
+                ret.days |= parse_item(item, 1, 31, &[], 0, "day of month", true)?;
+            }
+        }
+        Ok(ret)
+    }
+
+    fn matches(&self, date: Date) -> bool {
+        let day = date.day();
+        is_set(self.days, day)
+            || self.before_last.iter().any(|offset| date.days_in_month() - offset == day)
+            || (self.last_working_day && last_working_day_of_month(date) == day)
+            || self.nearest_working_days.iter().any(|target| nearest_working_day(date, *target) == Some(day))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct DayOfWeek {
+    /// Indexed by the offset from Sunday (Sunday is 0).
+    days: u64,
+    /// `5L`, the last Friday of the month
+    last: Vec<Weekday>,
+    /// `1#2`, the second Monday of the month
+    nth: Vec<(i8, Weekday)>,
+}
+
+impl DayOfWeek {
+    fn parse_weekday(s: &str) -> Result<Weekday, String> {
+        let value = parse_value(s, 0, 7, &WEEKDAY_NAMES, 0, "day of week")?;
+        Weekday::from_sunday_zero_offset(value % 7).map_err(|e| e.to_string())
+    }
+
+    fn parse(field: &str) -> Result<Self, String> {
+        let mut ret = DayOfWeek::default();
+        for item in field.split(',') {
+            let upper = item.to_ascii_uppercase();
+            if let Some((weekday, nth)) = upper.split_once('#') {
+                let nth = parse_value(nth, 1, 5, &[], 0, "weekday occurrence")?;
+                ret.nth.push((nth, Self::parse_weekday(weekday)?));
+            } else if let Some(weekday) = upper.strip_suffix('L').filter(|weekday| !weekday.is_empty()) {
+                ret.last.push(Self::parse_weekday(weekday)?);
+            } else {
+                let mask = parse_item(item, 0, 7, &WEEKDAY_NAMES, 0, "day of week", true)?;
+                // both 0 and 7 are Sunday
+                ret.days |= (mask | (mask >> 7)) & 0x7f;
+            }
+        }
+        Ok(ret)
+    }
+
+    fn matches(&self, date: Date) -> bool {
+        let weekday = date.weekday();
+        let day = date.day();
+        is_set(self.days, weekday.to_sunday_zero_offset())
+            || self.last.iter().any(|last| *last == weekday && day + 7 > date.days_in_month())
+            || self.nth.iter().any(|(nth, wd)| *wd == weekday && (day - 1) / 7 + 1 == *nth)
+    }
+}
+
+/// A parsed cron expression: five fields (minute, hour, day of month, month, day of week), or six with seconds first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronExpression {
+    source: String,
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    months: u64,
+    days_of_month: DayOfMonth,
+    days_of_week: DayOfWeek,
+    /// Whether the day of month or day of week field is `*` or `?`. When neither is, a day matches if either does.
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl FromStr for CronExpression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let source = s.split_whitespace().collect::<Vec<_>>().join(" ");
+        let expanded = match MACROS.iter().find(|(name, _)| name.eq_ignore_ascii_case(&source)) {
+            Some((_, fields)) => *fields,
+            None if source.starts_with('@') => return Err(format!("unknown cron macro '{source}'")),
+            None => &source,
+        };
+        let mut fields: Vec<&str> = expanded.split(' ').collect();
+        let seconds = match fields.len() {
+            5 => 1,
+            6 => parse_field(fields.remove(0), 0, 59, &[], 0, "second")?,
+            n => return Err(format!("expected 5 or 6 fields in the cron expression, got {n}")),
+        };
+        let any = |field: &str| field.starts_with('*') || field == "?";
+        let expression = CronExpression{
+            seconds,
+            minutes: parse_field(fields[0], 0, 59, &[], 0, "minute")?,
+            hours: parse_field(fields[1], 0, 23, &[], 0, "hour")?,
+            days_of_month: DayOfMonth::parse(fields[2])?,
+            months: parse_field(fields[3], 1, 12, &MONTH_NAMES, 1, "month")?,
+            days_of_week: DayOfWeek::parse(fields[4])?,
+            any_day_of_month: any(fields[2]),
+            any_day_of_week: any(fields[4]),
+            source,
+        };
+        // every combination of months and days comes around within 28 years
+        let from = Date::constant(2000, 1, 1).to_datetime(Time::midnight());
+        let until = Date::constant(2029, 1, 1).to_datetime(Time::midnight());
+        if expression.next_local(from, Some(until)).is_none() {
+            return Err(format!("the cron expression '{}' never fires", expression.source))
+        }
+        Ok(expression)
+    }
+}
+
+impl std::fmt::Display for CronExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+fn just_before(timestamp: Timestamp) -> Timestamp {
+    timestamp.checked_sub(SignedDuration::from_nanos(1)).unwrap_or(timestamp)
+}
+
+impl CronExpression {
+    fn matches_date(&self, date: Date) -> bool {
+        if !is_set(self.months, date.month()) {
+            return false
+        }
+        // as in Vixie cron, either day field is enough only when neither starts with `*` (or is `?`)
+        if self.any_day_of_month || self.any_day_of_week {
+            self.days_of_month.matches(date) && self.days_of_week.matches(date)
+        } else {
+            self.days_of_month.matches(date) || self.days_of_week.matches(date)
+        }
+    }
+
+    /// Whether every hour matches, in which case the repeated local times of a fold fire both times.
+    fn every_hour(&self) -> bool {
+        self.hours == (1 << 24) - 1
+    }
+
+    /// The first matching time of day at or after `from`.
+    fn next_time(&self, from: Time) -> Option<Time> {
+        let (mut hour, mut minute, mut second) = (from.hour(), from.minute(), from.second());
+        loop {
+            let next_hour = next_set(self.hours, hour)?;
+            if next_hour != hour {
+                (hour, minute, second) = (next_hour, 0, 0);
+            }
+            match next_set(self.minutes, minute) {
+                None => (hour, minute, second) = (hour + 1, 0, 0),
+                Some(next_minute) => {
+                    if next_minute != minute {
+                        (minute, second) = (next_minute, 0);
+                    }
+                    match next_set(self.seconds, second) {
+                        None => (minute, second) = (minute + 1, 0),
+                        Some(second) => return Time::new(hour, minute, second, 0).ok(),
+                    }
+                }
+            }
+        }
+    }
+
+    /// The last matching time of day at or before `upto`.
+    fn previous_time(&self, upto: Time) -> Option<Time> {
+        let (mut hour, mut minute, mut second) = (upto.hour(), upto.minute(), upto.second());
+        loop {
+            let previous_hour = previous_set(self.hours, hour)?;
+            if previous_hour != hour {
+                (hour, minute, second) = (previous_hour, 59, 59);
+            }
+            match previous_set(self.minutes, minute) {
+                None => (hour, minute, second) = (hour - 1, 59, 59),
+                Some(previous_minute) => {
+                    if previous_minute != minute {
+                        (minute, second) = (previous_minute, 59);
+                    }
+                    match previous_set(self.seconds, second) {
+                        None => (minute, second) = (minute - 1, 59),
+                        Some(second) => return Time::new(hour, minute, second, 0).ok(),
+                    }
+                }
+            }
+        }
+    }
+
+    /// The first matching local datetime at or after `from` (which must be a whole second) and before `until`.
+    fn next_local(&self, from: DateTime, until: Option<DateTime>) -> Option<DateTime> {
+        let mut date = from.date();
+        let mut time = from.time();
+        let last_year = from.year() as i32 + MAX_SEARCH_YEARS;
+        loop {
+            if until.is_some_and(|until| date > until.date()) || date.year() as i32 > last_year {
+                return None
+            }
+            if !is_set(self.months, date.month()) {
+                date = date.first_of_month().checked_add(1.month()).ok()?;
+                time = Time::midnight();
+                continue
+            }
+            if self.matches_date(date) {
+                if let Some(time) = self.next_time(time) {
+                    let found = date.to_datetime(time);
+                    return match until {
+                        Some(until) if found >= until => None,
+                        _ => Some(found)
+                    }
+                }
+            }
+            date = date.tomorrow().ok()?;
+            time = Time::midnight();
+        }
+    }
+
+    /// The last matching local datetime before `before` and at or after `from`.
+    fn previous_local(&self, before: DateTime, from: Option<DateTime>) -> Option<DateTime> {
+        let mut date = before.date();
+        let mut upto = before.time().with().subsec_nanosecond(0).build().ok()?;
+        if upto == before.time() {
+            match upto.checked_sub(SignedDuration::from_secs(1)) {
+                Ok(time) if time < upto => upto = time,
+                _ => {
+                    date = date.yesterday().ok()?;
+                    upto = Time::MAX;
+                }
+            }
+        }
+        let first_year = before.year() as i32 - MAX_SEARCH_YEARS;
+        loop {
+            if from.is_some_and(|from| date < from.date()) || (date.year() as i32) < first_year {
+                return None
+            }
+            if !is_set(self.months, date.month()) {
+                date = date.first_of_month().yesterday().ok()?;
+                upto = Time::MAX;
+                continue
+            }
+            if self.matches_date(date) {
+                if let Some(time) = self.previous_time(upto) {
+                    let found = date.to_datetime(time);
+                    return match from {
+                        Some(from) if found < from => None,
+                        _ => Some(found)
+                    }
+                }
+            }
+            date = date.yesterday().ok()?;
+            upto = Time::MAX;
+        }
+    }
+
+    /// The first fire time after `after`. The search goes through the time zone one offset at a time. Local times
+    /// skipped by a gap fire once, at the moment of the transition. Local times repeated by a fold fire only the first
+    /// time, unless the expression matches every hour.
+    pub fn next_after(&self, after: Timestamp, tz: &TimeZone) -> Option<Timestamp> {
+        let start = tz.to_offset(after).to_datetime(after);
+        let mut lower = start.with().subsec_nanosecond(0).build().ok()?.checked_add(SignedDuration::from_secs(1)).ok()?;
+        if !self.every_hour() {
+            if let Some(transition) = tz.preceding(after.checked_add(SignedDuration::from_nanos(1)).ok()?).next() {
+                let old_offset = tz.to_offset(just_before(transition.timestamp()));
+                if old_offset > transition.offset() {
+                    lower = lower.max(old_offset.to_datetime(transition.timestamp()));
+                }
+            }
+        }
+        let mut segment_start = after;
+        let last_year = start.year() as i32 + MAX_SEARCH_YEARS;
+        while (lower.year() as i32) <= last_year {
+            let offset = tz.to_offset(segment_start);
+            let transition = tz.following(segment_start).next();
+            let until = transition.as_ref().map(|transition| offset.to_datetime(transition.timestamp()));
+            if let Some(local) = self.next_local(lower, until) {
+                return offset.to_timestamp(local).ok()
+            }
+            let transition = transition?;
+            let at = transition.timestamp();
+            let next_offset = transition.offset();
+            if next_offset > offset && self.next_local(offset.to_datetime(at), Some(next_offset.to_datetime(at))).is_some() {
+                return Some(at)
+            }
+            lower = if next_offset < offset && !self.every_hour() {
+                offset.to_datetime(at)
+            } else {
+                next_offset.to_datetime(at)
+            };
+            segment_start = at;
+        }
+        None
+    }
+
+    /// The last fire time before `before`, by the same rules as [`CronExpression::next_after`].
+    pub fn previous_before(&self, before: Timestamp, tz: &TimeZone) -> Option<Timestamp> {
+        let mut segment_end = before;
+        let mut upper = tz.to_offset(just_before(before)).to_datetime(before);
+        let first_year = upper.year() as i32 - MAX_SEARCH_YEARS;
+        while upper.year() as i32 >= first_year {
+            let offset = tz.to_offset(just_before(segment_end));
+            let transition = tz.preceding(segment_end).next();
+            let (at, old_offset) = match transition {
+                None => return self.previous_local(upper, None).and_then(|local| offset.to_timestamp(local).ok()),
+                Some(transition) => (transition.timestamp(), tz.to_offset(just_before(transition.timestamp()))),
+            };
+            let lower = if old_offset > offset && !self.every_hour() {
+                old_offset.to_datetime(at)
+            } else {
+                offset.to_datetime(at)
+            };
+            if let Some(local) = self.previous_local(upper, Some(lower)) {
+                return offset.to_timestamp(local).ok()
+            }
+            if offset > old_offset && self.next_local(old_offset.to_datetime(at), Some(offset.to_datetime(at))).is_some() {
+                return Some(at)
+            }
+            upper = old_offset.to_datetime(at);
+            segment_end = at;
+        }
+        None
+    }
+}
+
+#[repr(C)]
+pub struct TempusCron {
+    expression: CronExpression,
+}
+
+impl TempusCron {
+    pub fn stuff_into(self, pointer: *mut *mut TempusCron) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn cron_parse(ahk_string: AHKWstr, out_cron: *mut *mut TempusCron) -> c_longlong {
+    match ahk_str_to_string(ahk_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(s) => {
+            match CronExpression::from_str(&s) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(expression) => {
+                    TempusCron{expression}.stuff_into(out_cron);
+                    0
+                }
+            }
+        }
+    }
+}
+
+/// The first fire time after the zoned datetime, in its time zone.
+#[no_mangle]
+pub extern "C" fn cron_next(tcron: &TempusCron, tzoned: &TempusZoned, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    let tz = tzoned.zoned.time_zone();
+    match tcron.expression.next_after(tzoned.zoned.timestamp(), tz) {
+        None => {
+            set_last_error_message(format!("'{}' does not fire after {}", tcron.expression, tzoned.zoned));
+            -1
+        }
+        Some(timestamp) => {
+            TempusZoned{zoned: timestamp.to_zoned(tz.clone())}.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+/// The last fire time before the zoned datetime, in its time zone.
+#[no_mangle]
+pub extern "C" fn cron_previous(tcron: &TempusCron, tzoned: &TempusZoned, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    let tz = tzoned.zoned.time_zone();
+    match tcron.expression.previous_before(tzoned.zoned.timestamp(), tz) {
+        None => {
+            set_last_error_message(format!("'{}' does not fire before {}", tcron.expression, tzoned.zoned));
+            -1
+        }
+        Some(timestamp) => {
+            TempusZoned{zoned: timestamp.to_zoned(tz.clone())}.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn cron_string_length(tcron: &TempusCron) -> usize {
+    tcron.expression.to_string().len()
+}
+
+#[no_mangle]
+pub extern "C" fn cron_to_string(tcron: &TempusCron, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(tcron.expression.to_string(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_cron(tcron: Box<TempusCron>) -> c_longlong {
+    let raw = Box::into_raw(tcron);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
@@ -2,16 +2,16 @@ use std::cmp::Ordering;
 use std::ffi::c_short;
 use std::ffi::{c_char, c_longlong};
 use std::str::FromStr;
-use jiff::civil::{Date, DateDifference, DateSeries, Era, Weekday};
-use jiff::{Error};
+use jiff::civil::{Time, Date, DateDifference, DateSeries, DateWith, Era, Weekday};
+use jiff::{Error, SignedDuration, Span, Unit};
 use jiff::fmt::strtime::BrokenDownTime;
-use crate::datetime::TempusDateTime;
+use crate::datetime::{datetime_end_of_period, datetime_start_of_period, TempusDateTime};
 use crate::duration::TempusSignedDuration;
 use crate::isoweekdate::TempusISOWeekDate;
 use crate::span::TempusSpan;
 use crate::time::TempusTime;
 use crate::tz::TempusTimeZone;
-use crate::utils::{ahk_str_to_string, round_mode_from_i8, set_last_error_message, string_into_ahk_buff, unit_from_i8, AHKStringBuffer, AHKWstr};
+use crate::utils::{expand_quarter_directive, first_week_from_i8, half_year_of_month, quarter_of_month, quarters_to_span, ahk_str_to_string, era_from_i8, narrow_field, overflow_from_i8, period_from_i8, round_mode_from_i8, ahk_time_of, parse_ahk_time, excel_date_system_from_i8, excel_serial_of, excel_serial_to_datetime, ole_date_of, ole_date_to_datetime, Overflow, Period, set_last_error_message, string_into_ahk_buff, unit_from_i8, AHKStringBuffer, AHKWstr};
 use crate::zoned::TempusZoned;
 
 #[repr(C)]
@@ -43,6 +43,11 @@ pub struct TempusDateSeries{
     series: DateSeries
 }
 
+#[repr(C)]
+pub struct TempusDateWith {
+    with: DateWith
+}
+
 
 #[no_mangle]
 pub extern "C" fn date_parse(ahk_time_string: AHKWstr, out_date: *mut *mut TempusDate) -> c_longlong {
@@ -66,6 +71,170 @@ pub extern "C" fn date_parse(ahk_time_string: AHKWstr, out_date: *mut *mut Tempu
     }
 }
 
+/// Parses AutoHotkey's YYYYMMDDHH24MISS format (like `A_Now`), which can be cut short after any field. The time of
+/// day is ignored.
+#[no_mangle]
+pub extern "C" fn date_from_ahk_time(ahk_time_string: AHKWstr, out_date: *mut *mut TempusDate) -> c_longlong {
+    match ahk_str_to_string(ahk_time_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(time_string) => {
+            match parse_ahk_time(&time_string).map(|datetime| TempusDate{date: datetime.date()}) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(value) => {
+                    value.stuff_into(out_date);
+                    0
+                }
+            }
+        }
+    }
+}
+
+/// The date as a YYYYMMDDHH24MISS timestamp at midnight, like 20250310000000.
+#[no_mangle]
+pub extern "C" fn date_to_ahk_time(td: &TempusDate, out_ahk_time: &mut i64) -> c_longlong {
+    match ahk_time_of(td.date.to_datetime(Time::midnight())) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(ahk_time) => {
+            *out_ahk_time = ahk_time;
+            0
+        }
+    }
+}
+
+/// Reads an OLE Automation date. The time of day is ignored.
+#[no_mangle]
+pub extern "C" fn date_from_ole_date(value: f64, out_date: *mut *mut TempusDate) -> c_longlong {
+    match ole_date_to_datetime(value).map(|datetime| TempusDate{date: datetime.date()}) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(value) => {
+            value.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+/// The date as an OLE Automation date (a whole number of days since 1899-12-30).
+#[no_mangle]
+pub extern "C" fn date_to_ole_date(td: &TempusDate, out_value: &mut f64) -> c_longlong {
+    match ole_date_of(td.date.to_datetime(Time::midnight())) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(value) => {
+            *out_value = value;
+            0
+        }
+    }
+}
+
+/// Reads an Excel serial date in the given date system (1 = 1900, 2 = 1904). The time of day is ignored.
+#[no_mangle]
+pub extern "C" fn date_from_excel_serial(serial: f64, date_system_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    match excel_date_system_from_i8(date_system_i).and_then(|system| excel_serial_to_datetime(serial, system)).map(|datetime| TempusDate{date: datetime.date()}) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(value) => {
+            value.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+/// The date as an Excel serial date in the given date system (1 = 1900, 2 = 1904).
+#[no_mangle]
+pub extern "C" fn date_to_excel_serial(td: &TempusDate, date_system_i: i8, out_value: &mut f64) -> c_longlong {
+    match excel_date_system_from_i8(date_system_i).and_then(|system| excel_serial_of(td.date.to_datetime(Time::midnight()), system)) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(value) => {
+            *out_value = value;
+            0
+        }
+    }
+}
+
+// The Julian Day Number, Modified Julian Day and Rata Die of 1970-01-01
+const JULIAN_DAY_UNIX_EPOCH: i64 = 2_440_588;
+const MODIFIED_JULIAN_DAY_UNIX_EPOCH: i64 = 40_587;
+pub(crate) const RATA_DIE_UNIX_EPOCH: i64 = 719_163;
+const UNIX_EPOCH: Date = Date::constant(1970, 1, 1);
+
+/// The date `day` days into a day count in which 1970-01-01 is day `unix_epoch_day`.
+pub(crate) fn date_of_day_count(day: i64, unix_epoch_day: i64) -> Result<Date, String> {
+    let seconds = day.checked_sub(unix_epoch_day).and_then(|days| days.checked_mul(86_400)).ok_or(format!("day {day} is out of range"))?;
+    UNIX_EPOCH.checked_add(SignedDuration::from_secs(seconds)).map_err(|e| e.to_string())
+}
+
+fn date_from_day_count(day: i64, unix_epoch_day: i64, out_date: *mut *mut TempusDate) -> c_longlong {
+    match date_of_day_count(day, unix_epoch_day) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            TempusDate{date}.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+/// The day of the date in a day count in which 1970-01-01 is day `unix_epoch_day`.
+pub(crate) fn day_count_of(date: Date, unix_epoch_day: i64) -> i64 {
+    unix_epoch_day + UNIX_EPOCH.duration_until(date).as_hours() / 24
+}
+
+/// Reads a Julian Day Number, the count of days since 4714-11-24 BCE in the proleptic Gregorian calendar (-4713-11-24 in
+/// ISO 8601's astronomical year numbering). Astronomers start Julian days at noon; this is the date that day's
+/// afternoon falls on.
+#[no_mangle]
+pub extern "C" fn date_from_julian_day(day: i64, out_date: *mut *mut TempusDate) -> c_longlong {
+    date_from_day_count(day, JULIAN_DAY_UNIX_EPOCH, out_date)
+}
+
+#[no_mangle]
+pub extern "C" fn date_to_julian_day(td: &TempusDate) -> c_longlong {
+    day_count_of(td.date, JULIAN_DAY_UNIX_EPOCH)
+}
+
+/// Reads a Modified Julian Day, the count of days since 1858-11-17. Unlike Julian days, these start at midnight.
+#[no_mangle]
+pub extern "C" fn date_from_modified_julian_day(day: i64, out_date: *mut *mut TempusDate) -> c_longlong {
+    date_from_day_count(day, MODIFIED_JULIAN_DAY_UNIX_EPOCH, out_date)
+}
+
+#[no_mangle]
+pub extern "C" fn date_to_modified_julian_day(td: &TempusDate) -> c_longlong {
+    day_count_of(td.date, MODIFIED_JULIAN_DAY_UNIX_EPOCH)
+}
+
+/// Reads a Rata Die, the count of days in which 0001-01-01 is day 1.
+#[no_mangle]
+pub extern "C" fn date_from_rata_die(day: i64, out_date: *mut *mut TempusDate) -> c_longlong {
+    date_from_day_count(day, RATA_DIE_UNIX_EPOCH, out_date)
+}
+
+#[no_mangle]
+pub extern "C" fn date_to_rata_die(td: &TempusDate) -> c_longlong {
+    day_count_of(td.date, RATA_DIE_UNIX_EPOCH)
+}
+
 #[no_mangle]
 pub extern "C" fn date_string_length(td: &TempusDate) -> usize {
     td.date.to_string().len()
@@ -117,6 +286,72 @@ pub extern "C" fn date_new(year: i16, month: i8, day: i8, out_date: *mut *mut Te
     }
 }
 
+/// Resolves year/month/day fields under the given overflow mode.
+pub(crate) fn date_from_fields(year: i64, month: i64, day: i64, overflow: Overflow) -> Result<Date, String> {
+    match overflow {
+        Overflow::Reject => {
+            Date::new(narrow_field(year, "year")?, narrow_field(month, "month")?, narrow_field(day, "day")?).map_err(|e| e.to_string())
+        }
+        Overflow::Constrain => {
+            let first = Date::new(narrow_field(year, "year")?, month.clamp(1, 12) as i8, 1).map_err(|e| e.to_string())?;
+            let day = day.clamp(1, first.days_in_month() as i64) as i8;
+            first.with().day(day).build().map_err(|e| e.to_string())
+        }
+        Overflow::Balance => {
+            let months = month.checked_sub(1).ok_or("month overflowed")?;
+            let year = year.checked_add(months.div_euclid(12)).ok_or("year overflowed")?;
+            let month = (months.rem_euclid(12) + 1) as i8;
+            let first = Date::new(narrow_field(year, "year")?, month, 1).map_err(|e| e.to_string())?;
+            let days = Span::new().try_days(day.checked_sub(1).ok_or("day overflowed")?).map_err(|e| e.to_string())?;
+            first.checked_add(days).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Adds a span to a date, resolving the day of month under the given overflow mode when the
+/// years and months of the span land on a month that is too short.
+pub(crate) fn date_add_span_overflow(date: Date, span: Span, overflow: Overflow) -> Result<Date, String> {
+    let calendar = Span::new().years(span.get_years()).months(span.get_months());
+    if overflow == Overflow::Constrain || calendar.is_zero() {
+        return date.checked_add(span).map_err(|e| e.to_string())
+    }
+    let mut shifted = date.checked_add(calendar).map_err(|e| e.to_string())?;
+    if shifted.day() != date.day() {
+        match overflow {
+            Overflow::Reject => {
+                return Err(format!("day {} does not exist in {:04}-{:02}", date.day(), shifted.year(), shifted.month()))
+            }
+            _ => {
+                let days = Span::new().days(date.day() - 1);
+                shifted = shifted.first_of_month().checked_add(days).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    shifted.checked_add(span.years(0).months(0)).map_err(|e| e.to_string())
+}
+
+#[no_mangle]
+pub extern "C" fn date_new_overflow(year: i64, month: i64, day: i64, overflow_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    let overflow = match overflow_from_i8(overflow_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(overflow) => overflow
+    };
+    match date_from_fields(year, month, day, overflow) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(date) => {
+            let td = TempusDate{date};
+            td.stuff_into(out_date);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn date_year(td: &TempusDate) -> c_short {
     td.date.year()
@@ -150,6 +385,7 @@ pub extern "C" fn date_strftime_length(td: &TempusDate, ahk_format_str: AHKWstr)
             -1
         }
         Ok(format_str) => {
+            let format_str = expand_quarter_directive(&format_str, td.date.month());
             let bdt = BrokenDownTime::from(td.date);
             let mut buf = String::new();
             match bdt.format(format_str, &mut buf) {
@@ -183,6 +419,7 @@ pub extern "C" fn date_strftime(td: &TempusDate, ahk_format_str: AHKWstr, out_bu
             -1
         }
         Ok(format_str) => {
+            let format_str = expand_quarter_directive(&format_str, td.date.month());
             let bdt = BrokenDownTime::from(td.date);
             let mut buf = String::new();
             match bdt.format(format_str, &mut buf) {
@@ -278,6 +515,82 @@ pub extern "C" fn date_last_of_year(td: &TempusDate) -> Box<TempusDate> {
     Box::new(TempusDate{date: td.date.last_of_year()})
 }
 
+#[no_mangle]
+pub extern "C" fn date_quarter(td: &TempusDate) -> c_char {
+    quarter_of_month(td.date.month())
+}
+
+#[no_mangle]
+pub extern "C" fn date_half_year(td: &TempusDate) -> c_char {
+    half_year_of_month(td.date.month())
+}
+
+#[no_mangle]
+pub extern "C" fn date_first_of_quarter(td: &TempusDate, out_date: *mut *mut TempusDate) -> c_longlong {
+    match date_start_of_period(td.date, Period::Quarter, Weekday::Monday) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            let new_date = TempusDate{date};
+            new_date.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_last_of_quarter(td: &TempusDate, out_date: *mut *mut TempusDate) -> c_longlong {
+    match date_end_of_period(td.date, Period::Quarter, Weekday::Monday) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            let new_date = TempusDate{date};
+            new_date.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_checked_add_quarters(td: &TempusDate, quarters: i64, out_date: *mut *mut TempusDate) -> c_longlong {
+    let span = match quarters_to_span(quarters) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(span) => span
+    };
+    match td.date.checked_add(span) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(date) => {
+            let new_date = TempusDate{date};
+            new_date.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_quarters_until(td: &TempusDate, other: &TempusDate, out_quarters: &mut i64) -> c_longlong {
+    match td.date.until((Unit::Month, other.date)) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(span) => {
+            *out_quarters = span.get_months() as i64 / 3;
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn date_days_in_month(td: &TempusDate) -> c_char {
     td.date.days_in_month()
@@ -345,6 +658,85 @@ pub extern "C" fn date_nth_weekday_of_month(td: &TempusDate, nth: i8, weekday_i:
     }
 }
 
+/// Easter Sunday in the given year (see `crate::holiday::easter`).
+#[no_mangle]
+pub extern "C" fn date_easter(year: i16, out_date: *mut *mut TempusDate) -> c_longlong {
+    match crate::holiday::easter(year) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            let td = TempusDate{date};
+            td.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_days_until_weekday(td: &TempusDate, weekday_i: i8) -> c_char {
+    match Weekday::from_sunday_one_offset(weekday_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(weekday) => {
+            td.date.weekday().until(weekday)
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_days_since_weekday(td: &TempusDate, weekday_i: i8) -> c_char {
+    match Weekday::from_sunday_one_offset(weekday_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(weekday) => {
+            td.date.weekday().since(weekday)
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_weekday_of_month_ordinal(td: &TempusDate) -> c_char {
+    (td.date.day() - 1) / 7 + 1
+}
+
+#[no_mangle]
+pub extern "C" fn date_is_last_weekday_of_month(td: &TempusDate) -> c_char {
+    (td.date.day() + 7 > td.date.days_in_month()) as i8
+}
+
+/// Counts how many times each weekday occurs between two dates, including both ends, in either order.
+/// The counts are indexed by the weekday's offset from Sunday (Sunday is 0).
+fn count_weekdays(start: Date, end: Date) -> Result<[i64; 7], String> {
+    let (start, end) = if start <= end { (start, end) } else { (end, start) };
+    let days = i64::from(start.until(end).map_err(|e| e.to_string())?.get_days()) + 1;
+    let mut counts = [days / 7; 7];
+    let first = start.weekday().to_sunday_zero_offset();
+    for offset in 0..(days % 7) as i8 {
+        counts[((first + offset) % 7) as usize] += 1;
+    }
+    Ok(counts)
+}
+
+#[no_mangle]
+pub extern "C" fn date_count_weekdays(td: &TempusDate, other: &TempusDate, out_counts: &mut [i64; 7]) -> c_longlong {
+    match count_weekdays(td.date, other.date) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(counts) => {
+            *out_counts = counts;
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn date_nth_weekday(td: &TempusDate, nth: i32, weekday_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
     let weekday = match Weekday::from_sunday_one_offset(weekday_i) {
@@ -372,6 +764,63 @@ pub extern "C" fn date_to_isoweekdate(td: &TempusDate) -> Box<TempusISOWeekDate>
     Box::new(TempusISOWeekDate{weekdate: td.date.iso_week_date()})
 }
 
+#[no_mangle]
+pub extern "C" fn date_week_of_year(td: &TempusDate, week_start_i: i8, first_week_i: i8, out_year: &mut i16, out_week: &mut i8) -> c_longlong {
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -1
+        }
+        Ok(weekday) => weekday
+    };
+    let first_week_days = match first_week_from_i8(first_week_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -2
+        }
+        Ok(days) => days
+    };
+    match week_of_year_of_date(td.date, week_start, first_week_days) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok((year, week)) => {
+            *out_year = year;
+            *out_week = week;
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_week_of_month(td: &TempusDate, week_start_i: i8, first_week_i: i8, out_week: &mut i8) -> c_longlong {
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -1
+        }
+        Ok(weekday) => weekday
+    };
+    let first_week_days = match first_week_from_i8(first_week_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -2
+        }
+        Ok(days) => days
+    };
+    match week_of_month_of_date(td.date, week_start, first_week_days) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(week) => {
+            *out_week = week;
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn date_in_tz(td: &TempusDate, time_zone_name: AHKWstr, out_zoned: *mut *mut TempusZoned) -> c_longlong {
     match ahk_str_to_string(time_zone_name) {
@@ -446,6 +895,50 @@ pub extern "C" fn date_checked_sub_span(td: &TempusDate, other: &TempusSpan, out
     }
 }
 
+#[no_mangle]
+pub extern "C" fn date_checked_add_span_overflow(td: &TempusDate, other: &TempusSpan, overflow_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    let overflow = match overflow_from_i8(overflow_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(overflow) => overflow
+    };
+    match date_add_span_overflow(td.date, other.span, overflow) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(date) => {
+            let tdate = TempusDate{date};
+            tdate.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_checked_sub_span_overflow(td: &TempusDate, other: &TempusSpan, overflow_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    let overflow = match overflow_from_i8(overflow_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(overflow) => overflow
+    };
+    match date_add_span_overflow(td.date, other.span.negate(), overflow) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(date) => {
+            let tdate = TempusDate{date};
+            tdate.stuff_into(out_date);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn date_checked_add_signed_duration(td: &TempusDate, other: &TempusSignedDuration, out_date: *mut *mut TempusDate) -> c_longlong {
     match td.date.checked_add(other.duration) {
@@ -720,6 +1213,208 @@ pub extern "C" fn date_series_next(tds: &mut TempusDateSeries, out_date: *mut *m
     }
 }
 
+/// Returns the first day of week 1 counting from `first` (the first day of a year or month), where week 1 is the
+/// first week starting on `week_start` that contains at least `first_week_days` days on or after `first`.
+fn first_day_of_week_one(first: Date, week_start: Weekday, first_week_days: i8) -> Result<Date, String> {
+    let offset = first.weekday().since(week_start);
+    let days = if 7 - offset >= first_week_days { -offset } else { 7 - offset };
+    first.checked_add(Span::new().days(days)).map_err(|e| e.to_string())
+}
+
+fn week_number(week_one: Date, date: Date) -> Result<i8, String> {
+    let days = week_one.until(date).map_err(|e| e.to_string())?.get_days();
+    Ok((days / 7 + 1) as i8)
+}
+
+/// Returns the week-numbering year and week of year of the given date. As with ISO weeks, the first days of January
+/// may belong to the last week of the previous year and the last days of December to week 1 of the next year.
+pub(crate) fn week_of_year_of_date(date: Date, week_start: Weekday, first_week_days: i8) -> Result<(i16, i8), String> {
+    let first = date.first_of_year();
+    let week_one = first_day_of_week_one(first, week_start, first_week_days)?;
+    if date < week_one {
+        let previous = first.checked_sub(Span::new().years(1)).map_err(|e| e.to_string())?;
+        let previous_week_one = first_day_of_week_one(previous, week_start, first_week_days)?;
+        return Ok((date.year() - 1, week_number(previous_week_one, date)?))
+    }
+    // there is no year after 9999, so the last days of that year cannot spill over into it
+    if let Ok(next) = first.checked_add(Span::new().years(1)) {
+        if date >= first_day_of_week_one(next, week_start, first_week_days)? {
+            return Ok((date.year() + 1, 1))
+        }
+    }
+    Ok((date.year(), week_number(week_one, date)?))
+}
+
+/// Returns the week of the month of the given date. Days before week 1 of the month are in week 0.
+pub(crate) fn week_of_month_of_date(date: Date, week_start: Weekday, first_week_days: i8) -> Result<i8, String> {
+    let week_one = first_day_of_week_one(date.first_of_month(), week_start, first_week_days)?;
+    if date < week_one {
+        return Ok(0)
+    }
+    week_number(week_one, date)
+}
+
+/// Returns the first day of the period containing the given date. Periods shorter than a day
+/// leave the date unchanged.
+pub(crate) fn date_start_of_period(date: Date, period: Period, week_start: Weekday) -> Result<Date, String> {
+    if period.is_time() {
+        return Ok(date)
+    }
+    Ok(datetime_start_of_period(date.to_datetime(Time::midnight()), period, week_start)?.date())
+}
+
+/// Returns the last day of the period containing the given date. Periods shorter than a day
+/// leave the date unchanged.
+pub(crate) fn date_end_of_period(date: Date, period: Period, week_start: Weekday) -> Result<Date, String> {
+    if period.is_time() {
+        return Ok(date)
+    }
+    Ok(datetime_end_of_period(date.to_datetime(Time::midnight()), period, week_start)?.date())
+}
+
+#[no_mangle]
+pub extern "C" fn date_truncate(td: &TempusDate, unit_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    let unit = match unit_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(unit) => unit
+    };
+    match date_start_of_period(td.date, Period::Unit(unit), Weekday::Monday) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(date) => {
+            let new_td = TempusDate{date};
+            new_td.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_start_of(td: &TempusDate, unit_i: i8, week_start_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    let period = match period_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(period) => period
+    };
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -2
+        }
+        Ok(weekday) => weekday
+    };
+    match date_start_of_period(td.date, period, week_start) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(date) => {
+            let new_td = TempusDate{date};
+            new_td.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_end_of(td: &TempusDate, unit_i: i8, week_start_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    let period = match period_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(period) => period
+    };
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -2
+        }
+        Ok(weekday) => weekday
+    };
+    match date_end_of_period(td.date, period, week_start) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(date) => {
+            let new_td = TempusDate{date};
+            new_td.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn date_with(td: &TempusDate) -> Box<TempusDateWith> {
+    Box::new(TempusDateWith{with: td.date.with()})
+}
+
+#[no_mangle]
+pub extern "C" fn date_with_year(tdw: &mut TempusDateWith, year: i16) -> c_longlong {
+    tdw.with = tdw.with.year(year);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn date_with_era_year(tdw: &mut TempusDateWith, year: i16, era_i: i8) -> c_longlong {
+    let era = match era_from_i8(era_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(era) => era
+    };
+    tdw.with = tdw.with.era_year(year, era);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn date_with_month(tdw: &mut TempusDateWith, month: i8) -> c_longlong {
+    tdw.with = tdw.with.month(month);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn date_with_day(tdw: &mut TempusDateWith, day: i8) -> c_longlong {
+    tdw.with = tdw.with.day(day);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn date_with_day_of_year(tdw: &mut TempusDateWith, day: i16) -> c_longlong {
+    tdw.with = tdw.with.day_of_year(day);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn date_with_day_of_year_no_leap(tdw: &mut TempusDateWith, day: i16) -> c_longlong {
+    tdw.with = tdw.with.day_of_year_no_leap(day);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn date_with_build(tdw: &TempusDateWith, out_date: *mut *mut TempusDate) -> c_longlong {
+    match tdw.with.build() {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(date) => {
+            let tdate = TempusDate{date};
+            tdate.stuff_into(out_date);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn free_date(tz: Box<TempusDate>) -> c_longlong {
     let raw = Box::into_raw(tz);
@@ -737,3 +1432,12 @@ pub extern "C" fn free_date_series(tz: Box<TempusDateSeries>) -> c_longlong {
     }
     0
 }
+
+#[no_mangle]
+pub extern "C" fn free_date_with(tdw: Box<TempusDateWith>) -> c_longlong {
+    let raw = Box::into_raw(tdw);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
@@ -0,0 +1,250 @@
+use std::ffi::{c_char, c_longlong};
+use jiff::Span;
+use jiff::civil::Date;
+use crate::date::TempusDate;
+use crate::range::{HalfOpen, RangeSplit};
+use crate::span::TempusSpan;
+use crate::utils::{set_last_error_message, string_into_ahk_buff, AHKStringBuffer};
+
+/// A range of dates. Closed ranges are stored as half-open ranges ending the day after their last day.
+#[repr(C)]
+pub struct TempusDateRange {
+    pub range: HalfOpen<Date>
+}
+
+impl TempusDateRange {
+    pub fn stuff_into(self, pointer: *mut *mut TempusDateRange) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+impl std::fmt::Display for TempusDateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {})", self.range.start, self.range.end)
+    }
+}
+
+#[repr(C)]
+pub struct TempusDateRangeSeries {
+    split: RangeSplit<Date>
+}
+
+impl TempusDateRangeSeries {
+    pub fn stuff_into(self, pointer: *mut *mut TempusDateRangeSeries) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+/// Creates a range from `start` to `end`. When `closed_i` is 1, `end` is included in the range; when it's 0, the
+/// range is half-open and stops just before `end`.
+#[no_mangle]
+pub extern "C" fn daterange_new(start: &TempusDate, end: &TempusDate, closed_i: i8, out_range: *mut *mut TempusDateRange) -> c_longlong {
+    let end = match closed_i {
+        0 => end.date,
+        1 => {
+            match end.date.checked_add(Span::new().days(1)) {
+                Err(e) => {
+                    set_last_error_message(e.to_string());
+                    return -2
+                }
+                Ok(end) => end
+            }
+        }
+        _ => {
+            set_last_error_message("invalid options".to_string());
+            return -1
+        }
+    };
+    match HalfOpen::new(start.date, end) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(range) => {
+            let new_range = TempusDateRange{range};
+            new_range.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn daterange_start(tr: &TempusDateRange) -> Box<TempusDate> {
+    Box::new(TempusDate{date: tr.range.start})
+}
+
+/// The end of the range, which is not itself in the range.
+#[no_mangle]
+pub extern "C" fn daterange_end(tr: &TempusDateRange) -> Box<TempusDate> {
+    Box::new(TempusDate{date: tr.range.end})
+}
+
+/// The last day in the range.
+#[no_mangle]
+pub extern "C" fn daterange_last(tr: &TempusDateRange, out_last: *mut *mut TempusDate) -> c_longlong {
+    if tr.range.is_empty() {
+        set_last_error_message("an empty range has no last value".to_string());
+        return -1
+    }
+    match tr.range.end.checked_sub(Span::new().days(1)) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(date) => {
+            let last = TempusDate{date};
+            last.stuff_into(out_last);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn daterange_is_empty(tr: &TempusDateRange) -> c_char {
+    tr.range.is_empty() as i8
+}
+
+#[no_mangle]
+pub extern "C" fn daterange_contains(tr: &TempusDateRange, value: &TempusDate) -> c_char {
+    tr.range.contains(value.date) as i8
+}
+
+#[no_mangle]
+pub extern "C" fn daterange_contains_range(tr: &TempusDateRange, other: &TempusDateRange) -> c_char {
+    tr.range.contains_range(&other.range) as i8
+}
+
+#[no_mangle]
+pub extern "C" fn daterange_overlaps(tr: &TempusDateRange, other: &TempusDateRange) -> c_char {
+    tr.range.overlaps(&other.range) as i8
+}
+
+#[no_mangle]
+pub extern "C" fn daterange_eq(tr: &TempusDateRange, other: &TempusDateRange) -> c_char {
+    (tr.range == other.range) as i8
+}
+
+/// Returns 1, without setting a range, when the ranges don't overlap.
+#[no_mangle]
+pub extern "C" fn daterange_intersection(tr: &TempusDateRange, other: &TempusDateRange, out_range: *mut *mut TempusDateRange) -> c_longlong {
+    match tr.range.intersection(&other.range) {
+        None => {
+            1
+        }
+        Some(range) => {
+            let new_range = TempusDateRange{range};
+            new_range.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+/// Returns 1, without setting a range, when the ranges neither overlap nor touch.
+#[no_mangle]
+pub extern "C" fn daterange_union(tr: &TempusDateRange, other: &TempusDateRange, out_range: *mut *mut TempusDateRange) -> c_longlong {
+    match tr.range.union(&other.range) {
+        None => {
+            1
+        }
+        Some(range) => {
+            let new_range = TempusDateRange{range};
+            new_range.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+/// Returns 1, without setting a range, when there is no gap between the ranges.
+#[no_mangle]
+pub extern "C" fn daterange_gap(tr: &TempusDateRange, other: &TempusDateRange, out_range: *mut *mut TempusDateRange) -> c_longlong {
+    match tr.range.gap(&other.range) {
+        None => {
+            1
+        }
+        Some(range) => {
+            let new_range = TempusDateRange{range};
+            new_range.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+/// The length of the range in days.
+#[no_mangle]
+pub extern "C" fn daterange_length(tr: &TempusDateRange, out_span: *mut *mut TempusSpan) -> c_longlong {
+    match tr.range.start.until(tr.range.end) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(span) => {
+            let tspan = TempusSpan{span};
+            tspan.stuff_into(out_span);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn daterange_split(tr: &TempusDateRange, tspan: &TempusSpan, out_series: *mut *mut TempusDateRangeSeries) -> c_longlong {
+    match RangeSplit::new(tr.range, tspan.span, |value: Date, span: Span| value.checked_add(span)) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(split) => {
+            let series = TempusDateRangeSeries{split};
+            series.stuff_into(out_series);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn daterange_series_next(trs: &mut TempusDateRangeSeries, out_range: *mut *mut TempusDateRange) -> c_char {
+    match trs.split.next() {
+        None => {
+            -1
+        }
+        Some(range) => {
+            let new_range = TempusDateRange{range};
+            new_range.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn daterange_string_length(tr: &TempusDateRange) -> usize {
+    tr.to_string().len()
+}
+
+#[no_mangle]
+pub extern "C" fn daterange_to_string(tr: &TempusDateRange, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(tr.to_string(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_daterange(tr: Box<TempusDateRange>) -> c_longlong {
+    let raw = Box::into_raw(tr);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_daterange_series(trs: Box<TempusDateRangeSeries>) -> c_longlong {
+    let raw = Box::into_raw(trs);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
@@ -1,16 +1,16 @@
 use std::cmp::Ordering;
 use std::ffi::{c_char, c_int, c_longlong, c_short};
 use std::str::FromStr;
-use jiff::civil::{DateTime, DateTimeDifference, DateTimeRound, DateTimeSeries, Era, Weekday};
-use jiff::Error;
+use jiff::civil::{Time, DateTime, DateTimeDifference, DateTimeRound, DateTimeSeries, DateTimeWith, Era, Weekday};
+use jiff::{Error, RoundMode, Span, Unit};
 use jiff::fmt::strtime::BrokenDownTime;
-use crate::date::TempusDate;
+use crate::date::{date_add_span_overflow, date_end_of_period, week_of_month_of_date, week_of_year_of_date, date_from_fields, date_start_of_period, TempusDate};
 use crate::duration::TempusSignedDuration;
 use crate::isoweekdate::TempusISOWeekDate;
 use crate::span::TempusSpan;
-use crate::time::TempusTime;
+use crate::time::{time_from_fields, TempusTime};
 use crate::tz::TempusTimeZone;
-use crate::utils::{ahk_str_to_string, round_mode_from_i8, set_last_error_message, string_into_ahk_buff, unit_from_i8, AHKStringBuffer, AHKWstr};
+use crate::utils::{expand_quarter_directive, first_week_from_i8, half_year_of_month, quarter_of_month, quarters_to_span, ahk_str_to_string, era_from_i8, overflow_from_i8, narrow_field, period_from_i8, round_mode_from_i8, ahk_time_of, parse_ahk_time, excel_date_system_from_i8, excel_serial_of, excel_serial_to_datetime, ole_date_of, ole_date_to_datetime, Overflow, Period, set_last_error_message, string_into_ahk_buff, unit_from_i8, AHKStringBuffer, AHKWstr};
 use crate::zoned::TempusZoned;
 
 #[repr(C)]
@@ -41,6 +41,12 @@ impl TempusDateTime {
 pub struct TempusDateTimeSeries {
     series: DateTimeSeries
 }
+
+#[repr(C)]
+pub struct TempusDateTimeWith {
+    with: DateTimeWith
+}
+
 #[no_mangle]
 pub extern "C" fn datetime_parse(ahk_time_string: AHKWstr, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
     match ahk_str_to_string(ahk_time_string) {
@@ -63,6 +69,209 @@ pub extern "C" fn datetime_parse(ahk_time_string: AHKWstr, out_datetime: *mut *m
     }
 }
 
+/// Parses AutoHotkey's YYYYMMDDHH24MISS format (like `A_Now`), which can be cut short after any field.
+#[no_mangle]
+pub extern "C" fn datetime_from_ahk_time(ahk_time_string: AHKWstr, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    match ahk_str_to_string(ahk_time_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(time_string) => {
+            match parse_ahk_time(&time_string).map(|datetime| TempusDateTime{datetime}) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(value) => {
+                    value.stuff_into(out_datetime);
+                    0
+                }
+            }
+        }
+    }
+}
+
+/// The datetime as a YYYYMMDDHH24MISS timestamp like 20250310143000. Fractional seconds are dropped.
+#[no_mangle]
+pub extern "C" fn datetime_to_ahk_time(tdt: &TempusDateTime, out_ahk_time: &mut i64) -> c_longlong {
+    match ahk_time_of(tdt.datetime) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(ahk_time) => {
+            *out_ahk_time = ahk_time;
+            0
+        }
+    }
+}
+
+/// The layout of a Windows SYSTEMTIME.
+#[repr(C)]
+pub struct WindowsSystemTime {
+    year: u16,
+    month: u16,
+    day_of_week: u16,
+    day: u16,
+    hour: u16,
+    minute: u16,
+    second: u16,
+    milliseconds: u16,
+}
+
+impl WindowsSystemTime {
+    fn to_datetime(&self) -> Result<DateTime, String> {
+        let year = narrow_field(self.year as i64, "year")?;
+        let month = narrow_field(self.month as i64, "month")?;
+        let day = narrow_field(self.day as i64, "day")?;
+        let hour = narrow_field(self.hour as i64, "hour")?;
+        let minute = narrow_field(self.minute as i64, "minute")?;
+        let second = narrow_field(self.second as i64, "second")?;
+        if self.milliseconds > 999 {
+            return Err(format!("parameter 'milliseconds' with value {} is not in the required range of 0..=999", self.milliseconds))
+        }
+        let nanosecond = self.milliseconds as i32 * 1_000_000;
+        DateTime::new(year, month, day, hour, minute, second, nanosecond).map_err(|e| e.to_string())
+    }
+}
+
+/// Reads a Windows SYSTEMTIME. As with `SystemTimeToFileTime`, the day of the week is ignored.
+#[no_mangle]
+pub extern "C" fn datetime_from_systemtime(systemtime: &WindowsSystemTime, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    match systemtime.to_datetime() {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(datetime) => {
+            TempusDateTime{datetime}.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+/// Writes the datetime into a Windows SYSTEMTIME, including the day of the week. Sub-millisecond precision is
+/// dropped.
+#[no_mangle]
+pub extern "C" fn datetime_to_systemtime(tdt: &TempusDateTime, out_systemtime: &mut WindowsSystemTime) -> c_longlong {
+    let datetime = tdt.datetime;
+    if datetime.year() < 1601 {
+        set_last_error_message(format!("{datetime} is before 1601, which SYSTEMTIME can't represent"));
+        return -1
+    }
+    *out_systemtime = WindowsSystemTime{
+        year: datetime.year() as u16,
+        month: datetime.month() as u16,
+        day_of_week: datetime.weekday().to_sunday_zero_offset() as u16,
+        day: datetime.day() as u16,
+        hour: datetime.hour() as u16,
+        minute: datetime.minute() as u16,
+        second: datetime.second() as u16,
+        milliseconds: datetime.millisecond() as u16,
+    };
+    0
+}
+
+/// Reads a packed DOS date and time, as in FAT directory entries and ZIP headers. The date holds the years since 1980
+/// (bits 9-15), the month (5-8) and the day (0-4); the time holds the hour (11-15), the minute (5-10) and the seconds
+/// divided by two (0-4).
+#[no_mangle]
+pub extern "C" fn datetime_from_dos(dos_date: u16, dos_time: u16, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    let year = 1980 + (dos_date >> 9) as i16;
+    let month = ((dos_date >> 5) & 0x0F) as i8;
+    let day = (dos_date & 0x1F) as i8;
+    let hour = (dos_time >> 11) as i8;
+    let minute = ((dos_time >> 5) & 0x3F) as i8;
+    let second = ((dos_time & 0x1F) * 2) as i8;
+    match DateTime::new(year, month, day, hour, minute, second, 0) {
+        Err(e) => {
+            set_last_error_message(format!("invalid DOS date {dos_date:#06x} and time {dos_time:#06x}: {e}"));
+            -1
+        }
+        Ok(datetime) => {
+            TempusDateTime{datetime}.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+/// Packs the datetime into a DOS date and time. DOS times count in two-second steps, so an odd second is rounded down
+/// and any fraction is dropped.
+#[no_mangle]
+pub extern "C" fn datetime_to_dos(tdt: &TempusDateTime, out_date: &mut u16, out_time: &mut u16) -> c_longlong {
+    let datetime = tdt.datetime;
+    if !(1980..=2107).contains(&datetime.year()) {
+        set_last_error_message(format!("{datetime} is outside 1980-2107, which a DOS date can't represent"));
+        return -1
+    }
+    *out_date = ((datetime.year() - 1980) as u16) << 9 | (datetime.month() as u16) << 5 | datetime.day() as u16;
+    *out_time = (datetime.hour() as u16) << 11 | (datetime.minute() as u16) << 5 | (datetime.second() / 2) as u16;
+    0
+}
+
+/// Reads an OLE Automation date (days since 1899-12-30, with the time of day as the fraction), as COM and VBScript use.
+/// The result is rounded to the nearest millisecond.
+#[no_mangle]
+pub extern "C" fn datetime_from_ole_date(value: f64, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    match ole_date_to_datetime(value).map(|datetime| TempusDateTime{datetime}) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(value) => {
+            value.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+/// The datetime as an OLE Automation date. Sub-millisecond precision is dropped.
+#[no_mangle]
+pub extern "C" fn datetime_to_ole_date(tdt: &TempusDateTime, out_value: &mut f64) -> c_longlong {
+    match ole_date_of(tdt.datetime) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(value) => {
+            *out_value = value;
+            0
+        }
+    }
+}
+
+/// Reads an Excel serial date in the given date system (1 = 1900, 2 = 1904), rounded to the nearest millisecond.
+#[no_mangle]
+pub extern "C" fn datetime_from_excel_serial(serial: f64, date_system_i: i8, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    match excel_date_system_from_i8(date_system_i).and_then(|system| excel_serial_to_datetime(serial, system)).map(|datetime| TempusDateTime{datetime}) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(value) => {
+            value.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+/// The datetime as an Excel serial date in the given date system (1 = 1900, 2 = 1904). Sub-millisecond precision is
+/// dropped.
+#[no_mangle]
+pub extern "C" fn datetime_to_excel_serial(tdt: &TempusDateTime, date_system_i: i8, out_value: &mut f64) -> c_longlong {
+    match excel_date_system_from_i8(date_system_i).and_then(|system| excel_serial_of(tdt.datetime, system)) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(value) => {
+            *out_value = value;
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn datetime_string_length(tdt: &TempusDateTime) -> usize {
     tdt.datetime.to_string().len()
@@ -120,6 +329,50 @@ pub extern "C" fn datetime_new(year: i16,
     }
 }
 
+/// Adds a span to a datetime, resolving the day of month under the given overflow mode when the
+/// years and months of the span land on a month that is too short.
+pub(crate) fn datetime_add_span_overflow(datetime: DateTime, span: Span, overflow: Overflow) -> Result<DateTime, String> {
+    let calendar = Span::new().years(span.get_years()).months(span.get_months());
+    let date = date_add_span_overflow(datetime.date(), calendar, overflow)?;
+    date.to_datetime(datetime.time()).checked_add(span.years(0).months(0)).map_err(|e| e.to_string())
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_new_overflow(year: i64,
+                                        month: i64,
+                                        day: i64,
+                                        hour: i64,
+                                        minute: i64,
+                                        second: i64,
+                                        subsec_nanosecond: i64,
+                                        overflow_i: i8,
+                                        out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    let overflow = match overflow_from_i8(overflow_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(overflow) => overflow
+    };
+    // overflow in the time fields carries into the day when balancing
+    let resolved = time_from_fields(hour, minute, second, subsec_nanosecond, overflow).and_then(|(carry, time)| {
+        let day = day.checked_add(carry).ok_or("day overflowed")?;
+        let date = date_from_fields(year, month, day, overflow)?;
+        Ok(DateTime::from_parts(date, time))
+    });
+    match resolved {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(datetime) => {
+            let tdt = TempusDateTime { datetime };
+            tdt.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn datetime_hour(tdt: &TempusDateTime) -> c_char {
     tdt.datetime.hour()
@@ -185,6 +438,7 @@ pub extern "C" fn datetime_strftime_length(tdt: &TempusDateTime, ahk_format_str:
             -1
         }
         Ok(format_str) => {
+            let format_str = expand_quarter_directive(&format_str, tdt.datetime.month());
             let bdt = BrokenDownTime::from(tdt.datetime);
             let mut buf = String::new();
             match bdt.format(format_str, &mut buf) {
@@ -218,6 +472,7 @@ pub extern "C" fn datetime_strftime(tdt: &TempusDateTime, ahk_format_str: AHKWst
             -1
         }
         Ok(format_str) => {
+            let format_str = expand_quarter_directive(&format_str, tdt.datetime.month());
             let bdt = BrokenDownTime::from(tdt.datetime);
             let mut buf = String::new();
             match bdt.format(format_str, &mut buf) {
@@ -324,6 +579,82 @@ pub extern "C" fn datetime_last_of_year(tdt: &TempusDateTime) -> Box<TempusDateT
     Box::new(TempusDateTime{datetime: tdt.datetime.last_of_year()})
 }
 
+#[no_mangle]
+pub extern "C" fn datetime_quarter(tdt: &TempusDateTime) -> c_char {
+    quarter_of_month(tdt.datetime.month())
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_half_year(tdt: &TempusDateTime) -> c_char {
+    half_year_of_month(tdt.datetime.month())
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_first_of_quarter(tdt: &TempusDateTime, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    match date_start_of_period(tdt.datetime.date(), Period::Quarter, Weekday::Monday) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            let new_datetime = TempusDateTime{datetime: DateTime::from_parts(date, tdt.datetime.time())};
+            new_datetime.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_last_of_quarter(tdt: &TempusDateTime, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    match date_end_of_period(tdt.datetime.date(), Period::Quarter, Weekday::Monday) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(date) => {
+            let new_datetime = TempusDateTime{datetime: DateTime::from_parts(date, tdt.datetime.time())};
+            new_datetime.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_checked_add_quarters(tdt: &TempusDateTime, quarters: i64, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    let span = match quarters_to_span(quarters) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(span) => span
+    };
+    match tdt.datetime.checked_add(span) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(datetime) => {
+            let new_datetime = TempusDateTime{datetime};
+            new_datetime.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_quarters_until(tdt: &TempusDateTime, other: &TempusDateTime, out_quarters: &mut i64) -> c_longlong {
+    match tdt.datetime.until((Unit::Month, other.datetime)) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(span) => {
+            *out_quarters = span.get_months() as i64 / 3;
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn datetime_days_in_month(tdt: &TempusDateTime) -> c_char {
     tdt.datetime.days_in_month()
@@ -391,6 +722,42 @@ pub extern "C" fn datetime_nth_weekday_of_month(tdt: &TempusDateTime, nth: i8, w
     }
 }
 
+#[no_mangle]
+pub extern "C" fn datetime_days_until_weekday(tdt: &TempusDateTime, weekday_i: i8) -> c_char {
+    match Weekday::from_sunday_one_offset(weekday_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(weekday) => {
+            tdt.datetime.weekday().until(weekday)
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_days_since_weekday(tdt: &TempusDateTime, weekday_i: i8) -> c_char {
+    match Weekday::from_sunday_one_offset(weekday_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(weekday) => {
+            tdt.datetime.weekday().since(weekday)
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_weekday_of_month_ordinal(tdt: &TempusDateTime) -> c_char {
+    (tdt.datetime.day() - 1) / 7 + 1
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_is_last_weekday_of_month(tdt: &TempusDateTime) -> c_char {
+    (tdt.datetime.day() + 7 > tdt.datetime.days_in_month()) as i8
+}
+
 #[no_mangle]
 pub extern "C" fn datetime_nth_weekday(tdt: &TempusDateTime, nth: i32, weekday_i: i8, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
     let weekday = match Weekday::from_sunday_one_offset(weekday_i) {
@@ -418,6 +785,63 @@ pub extern "C" fn datetime_to_isoweekdate(tdt: &TempusDateTime) -> Box<TempusISO
     Box::new(TempusISOWeekDate{weekdate: tdt.datetime.iso_week_date()})
 }
 
+#[no_mangle]
+pub extern "C" fn datetime_week_of_year(tdt: &TempusDateTime, week_start_i: i8, first_week_i: i8, out_year: &mut i16, out_week: &mut i8) -> c_longlong {
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -1
+        }
+        Ok(weekday) => weekday
+    };
+    let first_week_days = match first_week_from_i8(first_week_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -2
+        }
+        Ok(days) => days
+    };
+    match week_of_year_of_date(tdt.datetime.date(), week_start, first_week_days) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok((year, week)) => {
+            *out_year = year;
+            *out_week = week;
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_week_of_month(tdt: &TempusDateTime, week_start_i: i8, first_week_i: i8, out_week: &mut i8) -> c_longlong {
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -1
+        }
+        Ok(weekday) => weekday
+    };
+    let first_week_days = match first_week_from_i8(first_week_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -2
+        }
+        Ok(days) => days
+    };
+    match week_of_month_of_date(tdt.datetime.date(), week_start, first_week_days) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(week) => {
+            *out_week = week;
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn datetime_to_date(tdt: &TempusDateTime) -> Box<TempusDate> {
     Box::new(TempusDate{date: tdt.datetime.date()})
@@ -476,6 +900,50 @@ pub extern "C" fn datetime_checked_sub_span(tdt: &TempusDateTime, other: &Tempus
 }
 
 
+#[no_mangle]
+pub extern "C" fn datetime_checked_add_span_overflow(tdt: &TempusDateTime, other: &TempusSpan, overflow_i: i8, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    let overflow = match overflow_from_i8(overflow_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(overflow) => overflow
+    };
+    match datetime_add_span_overflow(tdt.datetime, other.span, overflow) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(datetime) => {
+            let tdate = TempusDateTime{datetime};
+            tdate.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_checked_sub_span_overflow(tdt: &TempusDateTime, other: &TempusSpan, overflow_i: i8, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    let overflow = match overflow_from_i8(overflow_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(overflow) => overflow
+    };
+    match datetime_add_span_overflow(tdt.datetime, other.span.negate(), overflow) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(datetime) => {
+            let tdate = TempusDateTime{datetime};
+            tdate.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn datetime_checked_add_signed_duration(tdt: &TempusDateTime, other: &TempusSignedDuration, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
     match tdt.datetime.checked_add(other.duration) {
@@ -783,6 +1251,237 @@ pub extern "C" fn datetime_round(tdt: &TempusDateTime, smallest_i: i8, increment
     }
 }
 
+/// Returns the first instant of the period containing the given datetime. Weeks begin on `week_start`.
+pub(crate) fn datetime_start_of_period(datetime: DateTime, period: Period, week_start: Weekday) -> Result<DateTime, String> {
+    let date = datetime.date();
+    let start = match period {
+        Period::Unit(Unit::Year) => date.first_of_year(),
+        Period::HalfYear => {
+            let month = if date.month() <= 6 { 1 } else { 7 };
+            date.with().month(month).day(1).build().map_err(|e| e.to_string())?
+        }
+        Period::Quarter => {
+            let month = (date.month() - 1) / 3 * 3 + 1;
+            date.with().month(month).day(1).build().map_err(|e| e.to_string())?
+        }
+        Period::Unit(Unit::Month) => date.first_of_month(),
+        Period::Unit(Unit::Week) => {
+            let days = date.weekday().since(week_start);
+            date.checked_sub(Span::new().days(days)).map_err(|e| e.to_string())?
+        }
+        Period::Unit(Unit::Day) => date,
+        Period::Unit(unit) => {
+            let rounder = DateTimeRound::new().smallest(unit).mode(RoundMode::Trunc);
+            return datetime.round(rounder).map_err(|e| e.to_string())
+        }
+    };
+    Ok(start.to_datetime(Time::midnight()))
+}
+
+/// Returns the last nanosecond of the period containing the given datetime.
+pub(crate) fn datetime_end_of_period(datetime: DateTime, period: Period, week_start: Weekday) -> Result<DateTime, String> {
+    let start = datetime_start_of_period(datetime, period, week_start)?;
+    let next = start.checked_add(period.span()).map_err(|e| e.to_string())?;
+    next.checked_sub(Span::new().nanoseconds(1)).map_err(|e| e.to_string())
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_truncate(tdt: &TempusDateTime, unit_i: i8, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    let unit = match unit_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(unit) => unit
+    };
+    match datetime_start_of_period(tdt.datetime, Period::Unit(unit), Weekday::Monday) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(datetime) => {
+            let new_tdt = TempusDateTime{datetime};
+            new_tdt.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_start_of(tdt: &TempusDateTime, unit_i: i8, week_start_i: i8, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    let period = match period_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(period) => period
+    };
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -2
+        }
+        Ok(weekday) => weekday
+    };
+    match datetime_start_of_period(tdt.datetime, period, week_start) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(datetime) => {
+            let new_tdt = TempusDateTime{datetime};
+            new_tdt.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_end_of(tdt: &TempusDateTime, unit_i: i8, week_start_i: i8, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    let period = match period_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(period) => period
+    };
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -2
+        }
+        Ok(weekday) => weekday
+    };
+    match datetime_end_of_period(tdt.datetime, period, week_start) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(datetime) => {
+            let new_tdt = TempusDateTime{datetime};
+            new_tdt.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with(tdt: &TempusDateTime) -> Box<TempusDateTimeWith> {
+    Box::new(TempusDateTimeWith{with: tdt.datetime.with()})
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_date(tdtw: &mut TempusDateTimeWith, td: &TempusDate) -> c_longlong {
+    tdtw.with = tdtw.with.date(td.date);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_time(tdtw: &mut TempusDateTimeWith, tt: &TempusTime) -> c_longlong {
+    tdtw.with = tdtw.with.time(tt.time);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_year(tdtw: &mut TempusDateTimeWith, year: i16) -> c_longlong {
+    tdtw.with = tdtw.with.year(year);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_era_year(tdtw: &mut TempusDateTimeWith, year: i16, era_i: i8) -> c_longlong {
+    let era = match era_from_i8(era_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(era) => era
+    };
+    tdtw.with = tdtw.with.era_year(year, era);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_month(tdtw: &mut TempusDateTimeWith, month: i8) -> c_longlong {
+    tdtw.with = tdtw.with.month(month);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_day(tdtw: &mut TempusDateTimeWith, day: i8) -> c_longlong {
+    tdtw.with = tdtw.with.day(day);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_day_of_year(tdtw: &mut TempusDateTimeWith, day: i16) -> c_longlong {
+    tdtw.with = tdtw.with.day_of_year(day);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_day_of_year_no_leap(tdtw: &mut TempusDateTimeWith, day: i16) -> c_longlong {
+    tdtw.with = tdtw.with.day_of_year_no_leap(day);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_hour(tdtw: &mut TempusDateTimeWith, hour: i8) -> c_longlong {
+    tdtw.with = tdtw.with.hour(hour);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_minute(tdtw: &mut TempusDateTimeWith, minute: i8) -> c_longlong {
+    tdtw.with = tdtw.with.minute(minute);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_second(tdtw: &mut TempusDateTimeWith, second: i8) -> c_longlong {
+    tdtw.with = tdtw.with.second(second);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_millisecond(tdtw: &mut TempusDateTimeWith, millisecond: i16) -> c_longlong {
+    tdtw.with = tdtw.with.millisecond(millisecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_microsecond(tdtw: &mut TempusDateTimeWith, microsecond: i16) -> c_longlong {
+    tdtw.with = tdtw.with.microsecond(microsecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_nanosecond(tdtw: &mut TempusDateTimeWith, nanosecond: i16) -> c_longlong {
+    tdtw.with = tdtw.with.nanosecond(nanosecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_subsec_nanosecond(tdtw: &mut TempusDateTimeWith, subsec_nanosecond: i32) -> c_longlong {
+    tdtw.with = tdtw.with.subsec_nanosecond(subsec_nanosecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn datetime_with_build(tdtw: &TempusDateTimeWith, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    match tdtw.with.build() {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(datetime) => {
+            let tdt = TempusDateTime{datetime};
+            tdt.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn free_datetime(tdt: Box<TempusDateTime>) -> c_longlong {
     let raw = Box::into_raw(tdt);
@@ -800,3 +1499,12 @@ pub extern "C" fn free_datetime_series(tdt: Box<TempusDateTimeSeries>) -> c_long
     }
     0
 }
+
+#[no_mangle]
+pub extern "C" fn free_datetime_with(tdtw: Box<TempusDateTimeWith>) -> c_longlong {
+    let raw = Box::into_raw(tdtw);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
@@ -0,0 +1,331 @@
+use std::ffi::c_longlong;
+use jiff::civil::{Date, Weekday};
+use jiff::Span;
+use crate::date::TempusDate;
+use crate::daterange::TempusDateRange;
+use crate::range::HalfOpen;
+use crate::utils::{fiscal_year_end_from_i8, fiscal_year_name_from_i8, set_last_error_message, FiscalYearEnd, FiscalYearName};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FiscalRule {
+    /// Fiscal years of twelve calendar months, starting on the first of `start_month`.
+    Months { start_month: i8 },
+    /// 52/53-week fiscal years ending on a weekday at the end of `end_month`. Each quarter is split into periods of
+    /// `pattern` weeks (4-4-5, 4-5-4 or 5-4-4), and the extra week of a 53-week year goes to the last period.
+    Weeks { end_month: i8, weekday: Weekday, year_end: FiscalYearEnd, pattern: [i8; 3] },
+}
+
+/// A fiscal calendar. Internally, fiscal years are keyed by the calendar year of their year-end month (or the month
+/// before their start month), whatever they're named after.
+#[repr(C)]
+pub struct TempusFiscalCalendar {
+    rule: FiscalRule,
+    name: FiscalYearName,
+}
+
+fn days(n: i64) -> Span {
+    Span::new().days(n)
+}
+
+impl TempusFiscalCalendar {
+    pub fn stuff_into(self, pointer: *mut *mut TempusFiscalCalendar) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+
+    fn year_end(end_month: i8, weekday: Weekday, year_end: FiscalYearEnd, year: i16) -> Result<Date, String> {
+        let last_day = Date::new(year, end_month, 1).map_err(|e| e.to_string())?.last_of_month();
+        match year_end {
+            FiscalYearEnd::LastWeekday => last_day.nth_weekday_of_month(-1, weekday).map_err(|e| e.to_string()),
+            FiscalYearEnd::NearestWeekday => {
+                let days_back = (last_day.weekday().to_sunday_zero_offset() - weekday.to_sunday_zero_offset()).rem_euclid(7) as i64;
+                let shift = if days_back <= 3 { -days_back } else { 7 - days_back };
+                last_day.checked_add(days(shift)).map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    /// The fiscal year with the given key, as a range of dates.
+    fn bounds(&self, key: i16) -> Result<HalfOpen<Date>, String> {
+        let previous = key.checked_sub(1).ok_or("fiscal year out of range")?;
+        match self.rule {
+            FiscalRule::Months{start_month: 1} => {
+                let start = Date::new(key, 1, 1).map_err(|e| e.to_string())?;
+                let end = start.checked_add(Span::new().years(1)).map_err(|e| e.to_string())?;
+                Ok(HalfOpen{start, end})
+            }
+            FiscalRule::Months{start_month} => {
+                let start = Date::new(previous, start_month, 1).map_err(|e| e.to_string())?;
+                let end = Date::new(key, start_month, 1).map_err(|e| e.to_string())?;
+                Ok(HalfOpen{start, end})
+            }
+            FiscalRule::Weeks{end_month, weekday, year_end, ..} => {
+                let start = Self::year_end(end_month, weekday, year_end, previous)?.tomorrow().map_err(|e| e.to_string())?;
+                let end = Self::year_end(end_month, weekday, year_end, key)?.tomorrow().map_err(|e| e.to_string())?;
+                Ok(HalfOpen{start, end})
+            }
+        }
+    }
+
+    /// The key and bounds of the fiscal year that contains the date.
+    fn year_of(&self, date: Date) -> Result<(i16, HalfOpen<Date>), String> {
+        for key in [date.year().checked_sub(1), Some(date.year()), date.year().checked_add(1)].into_iter().flatten() {
+            if let Ok(bounds) = self.bounds(key) {
+                if bounds.contains(date) {
+                    return Ok((key, bounds))
+                }
+            }
+        }
+        Err(format!("{date} is outside of the supported range of fiscal years"))
+    }
+
+    /// Whether the fiscal year nominally ends in December, so that it starts in the same calendar year it ends in.
+    fn ends_in_december(&self) -> bool {
+        match self.rule {
+            FiscalRule::Months{start_month} => start_month == 1,
+            FiscalRule::Weeks{end_month, ..} => end_month == 12,
+        }
+    }
+
+    /// The name of the fiscal year with the given key. Start-year names go by the nominal start month, so a 52/53-week
+    /// year that starts in late December is still named after the January it's meant to start in.
+    fn name_of(&self, key: i16) -> i16 {
+        match self.name {
+            FiscalYearName::StartYear if !self.ends_in_december() => key - 1,
+            _ => key,
+        }
+    }
+
+    fn key_of(&self, fiscal_year: i16) -> Result<(i16, HalfOpen<Date>), String> {
+        let key = match self.name {
+            FiscalYearName::StartYear if !self.ends_in_december() => fiscal_year.checked_add(1),
+            _ => Some(fiscal_year),
+        };
+        match key.map(|key| (key, self.bounds(key))) {
+            Some((key, Ok(bounds))) => Ok((key, bounds)),
+            _ => Err(format!("fiscal year {fiscal_year} is out of range")),
+        }
+    }
+
+    fn days_between(start: Date, end: Date) -> Result<i64, String> {
+        Ok(start.until(end).map_err(|e| e.to_string())?.get_days() as i64)
+    }
+
+    fn weeks_in(bounds: HalfOpen<Date>) -> Result<i8, String> {
+        let length = Self::days_between(bounds.start, bounds.end)?;
+        Ok(((length + 6) / 7) as i8)
+    }
+
+    /// The number of weeks from the start of the year to the end of each period, for a 52/53-week year.
+    fn period_ends(pattern: [i8; 3], weeks: i8) -> [i8; 12] {
+        let mut ends = [0; 12];
+        let mut total = 0;
+        for (period, end) in ends.iter_mut().enumerate() {
+            total += pattern[period % 3];
+            *end = total;
+        }
+        ends[11] = weeks;
+        ends
+    }
+
+    /// The fiscal year (by name), quarter, period and week of a date.
+    pub fn fields(&self, date: Date) -> Result<(i16, i8, i8, i8), String> {
+        let (key, bounds) = self.year_of(date)?;
+        let days_in = Self::days_between(bounds.start, date)?;
+        let week = (days_in / 7 + 1) as i8;
+        let period = match self.rule {
+            FiscalRule::Months{..} => {
+                let months = (date.year() as i32 * 12 + date.month() as i32) - (bounds.start.year() as i32 * 12 + bounds.start.month() as i32);
+                (months + 1) as i8
+            }
+            FiscalRule::Weeks{pattern, ..} => {
+                let ends = Self::period_ends(pattern, Self::weeks_in(bounds)?);
+                ends.iter().position(|end| week <= *end).unwrap_or(11) as i8 + 1
+            }
+        };
+        Ok((self.name_of(key), (period - 1) / 3 + 1, period, week))
+    }
+
+    pub fn weeks_in_year(&self, fiscal_year: i16) -> Result<i8, String> {
+        let (_, bounds) = self.key_of(fiscal_year)?;
+        Self::weeks_in(bounds)
+    }
+
+    pub fn year_range(&self, fiscal_year: i16) -> Result<HalfOpen<Date>, String> {
+        Ok(self.key_of(fiscal_year)?.1)
+    }
+
+    pub fn period_range(&self, fiscal_year: i16, period: i8) -> Result<HalfOpen<Date>, String> {
+        if !(1..=12).contains(&period) {
+            return Err(format!("invalid fiscal period: {period}. Must be in range 1-12 (inclusive)"))
+        }
+        let (_, bounds) = self.key_of(fiscal_year)?;
+        let (start, end) = match self.rule {
+            FiscalRule::Months{..} => {
+                let start = bounds.start.checked_add(Span::new().months(period - 1)).map_err(|e| e.to_string())?;
+                let end = bounds.start.checked_add(Span::new().months(period)).map_err(|e| e.to_string())?;
+                (start, end)
+            }
+            FiscalRule::Weeks{pattern, ..} => {
+                let ends = Self::period_ends(pattern, Self::weeks_in(bounds)?);
+                let start_week = if period == 1 { 0 } else { ends[period as usize - 2] };
+                let start = bounds.start.checked_add(days(start_week as i64 * 7)).map_err(|e| e.to_string())?;
+                let end = bounds.start.checked_add(days(ends[period as usize - 1] as i64 * 7)).map_err(|e| e.to_string())?;
+                (start, end)
+            }
+        };
+        Ok(HalfOpen{start, end})
+    }
+
+    pub fn quarter_range(&self, fiscal_year: i16, quarter: i8) -> Result<HalfOpen<Date>, String> {
+        if !(1..=4).contains(&quarter) {
+            return Err(format!("invalid fiscal quarter: {quarter}. Must be in range 1-4 (inclusive)"))
+        }
+        let start = self.period_range(fiscal_year, quarter * 3 - 2)?.start;
+        let end = self.period_range(fiscal_year, quarter * 3)?.end;
+        Ok(HalfOpen{start, end})
+    }
+
+    /// A week of the fiscal year. The last week of a fiscal year of calendar months can be shorter than seven days.
+    pub fn week_range(&self, fiscal_year: i16, week: i8) -> Result<HalfOpen<Date>, String> {
+        let (_, bounds) = self.key_of(fiscal_year)?;
+        let weeks = Self::weeks_in(bounds)?;
+        if week < 1 || week > weeks {
+            return Err(format!("invalid fiscal week: {week}. Fiscal year {fiscal_year} has {weeks} weeks"))
+        }
+        let start = bounds.start.checked_add(days((week as i64 - 1) * 7)).map_err(|e| e.to_string())?;
+        let end = bounds.start.checked_add(days(week as i64 * 7)).map_err(|e| e.to_string())?.min(bounds.end);
+        Ok(HalfOpen{start, end})
+    }
+}
+
+/// A fiscal calendar of calendar months, with years starting on the first of `start_month`.
+#[no_mangle]
+pub extern "C" fn fiscal_calendar_new_months(start_month: i8, name_i: i8, out_calendar: *mut *mut TempusFiscalCalendar) -> c_longlong {
+    if !(1..=12).contains(&start_month) {
+        set_last_error_message(format!("invalid month: {start_month}. Must be in range 1-12 (inclusive)"));
+        return -1
+    }
+    let name = match fiscal_year_name_from_i8(name_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -2
+        }
+        Ok(name) => name
+    };
+    let tcal = TempusFiscalCalendar{rule: FiscalRule::Months{start_month}, name};
+    tcal.stuff_into(out_calendar);
+    0
+}
+
+/// A 52/53-week fiscal calendar. `pattern` is the number of weeks in each period of a quarter, written as a
+/// three-digit number: 445, 454 or 544.
+#[no_mangle]
+pub extern "C" fn fiscal_calendar_new_weeks(end_month: i8, weekday_i: i8, year_end_i: i8, pattern: i16, name_i: i8, out_calendar: *mut *mut TempusFiscalCalendar) -> c_longlong {
+    if !(1..=12).contains(&end_month) {
+        set_last_error_message(format!("invalid month: {end_month}. Must be in range 1-12 (inclusive)"));
+        return -1
+    }
+    let weekday = match Weekday::from_sunday_one_offset(weekday_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -2
+        }
+        Ok(weekday) => weekday
+    };
+    let year_end = match fiscal_year_end_from_i8(year_end_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -3
+        }
+        Ok(year_end) => year_end
+    };
+    let pattern = match pattern {
+        445 => [4, 4, 5],
+        454 => [4, 5, 4],
+        544 => [5, 4, 4],
+        _ => {
+            set_last_error_message(format!("invalid fiscal period pattern: {pattern}. Must be 445, 454 or 544"));
+            return -4
+        }
+    };
+    let name = match fiscal_year_name_from_i8(name_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -5
+        }
+        Ok(name) => name
+    };
+    let tcal = TempusFiscalCalendar{rule: FiscalRule::Weeks{end_month, weekday, year_end, pattern}, name};
+    tcal.stuff_into(out_calendar);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn fiscal_calendar_fields(tcal: &TempusFiscalCalendar, tdate: &TempusDate, out_year: &mut i16, out_quarter: &mut i8, out_period: &mut i8, out_week: &mut i8) -> c_longlong {
+    match tcal.fields(tdate.date) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok((year, quarter, period, week)) => {
+            *out_year = year;
+            *out_quarter = quarter;
+            *out_period = period;
+            *out_week = week;
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn fiscal_calendar_weeks_in_year(tcal: &TempusFiscalCalendar, fiscal_year: i16, out_weeks: &mut i8) -> c_longlong {
+    match tcal.weeks_in_year(fiscal_year) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(weeks) => {
+            *out_weeks = weeks;
+            0
+        }
+    }
+}
+
+/// The dates of a fiscal year (`unit_i` = 1), or of one of its quarters (2), periods (3) or weeks (4).
+/// `number` is ignored for a whole year.
+#[no_mangle]
+pub extern "C" fn fiscal_calendar_range(tcal: &TempusFiscalCalendar, fiscal_year: i16, unit_i: i8, number: i8, out_range: *mut *mut TempusDateRange) -> c_longlong {
+    let result = match unit_i {
+        1 => tcal.year_range(fiscal_year),
+        2 => tcal.quarter_range(fiscal_year, number),
+        3 => tcal.period_range(fiscal_year, number),
+        4 => tcal.week_range(fiscal_year, number),
+        _ => {
+            set_last_error_message(format!("invalid fiscal unit: {unit_i}. Must be in range 1-4 (inclusive)"));
+            return -1
+        }
+    };
+    match result {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(range) => {
+            let trange = TempusDateRange{range};
+            trange.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn free_fiscal_calendar(tcal: Box<TempusFiscalCalendar>) -> c_longlong {
+    let raw = Box::into_raw(tcal);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
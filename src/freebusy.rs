@@ -0,0 +1,203 @@
+use std::ffi::c_longlong;
+use jiff::civil::Time;
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Timestamp};
+use crate::duration::TempusSignedDuration;
+use crate::range::{intersect_sets, normalize, subtract_sets, HalfOpen};
+use crate::time::TempusTime;
+use crate::timestamprange::TempusTimestampRange;
+use crate::tz::TempusTimeZone;
+use crate::utils::set_last_error_message;
+use crate::zoned::TempusZoned;
+
+/// Someone whose calendar is taken into account when looking for free time. Their working hours are in their own
+/// time zone, so each day's window is resolved separately and follows that zone's DST transitions.
+struct Participant {
+    tz: TimeZone,
+    work_start: Time,
+    work_end: Time,
+    /// Indexed by the weekday's offset from Sunday (Sunday is 0).
+    workdays: [bool; 7],
+    busy: Vec<HalfOpen<Timestamp>>,
+}
+
+impl Participant {
+    /// The participant's working hours that fall within `search`. A window that ends at or before the time it
+    /// starts runs past midnight into the next day.
+    fn working_windows(&self, search: HalfOpen<Timestamp>) -> Result<Vec<HalfOpen<Timestamp>>, String> {
+        let first = search.start.to_zoned(self.tz.clone()).date();
+        // a window that started the day before can still be running at the start of the search
+        let first = first.yesterday().unwrap_or(first);
+        let last = search.end.to_zoned(self.tz.clone()).date();
+        let mut windows = Vec::new();
+        let mut date = first;
+        while date <= last {
+            if self.workdays[date.weekday().to_sunday_zero_offset() as usize] {
+                let end_date = if self.work_end <= self.work_start {
+                    date.tomorrow().map_err(|e| e.to_string())?
+                } else {
+                    date
+                };
+                let start = date.to_datetime(self.work_start).to_zoned(self.tz.clone()).map_err(|e| e.to_string())?;
+                let end = end_date.to_datetime(self.work_end).to_zoned(self.tz.clone()).map_err(|e| e.to_string())?;
+                let window = HalfOpen{start: start.timestamp(), end: end.timestamp()};
+                if let Some(window) = window.intersection(&search) {
+                    windows.push(window);
+                }
+            }
+            date = match date.tomorrow() {
+                Ok(next) => next,
+                Err(_) => break,
+            };
+        }
+        Ok(normalize(windows))
+    }
+}
+
+/// Finds the times when every participant is within their working hours and nobody is busy.
+#[repr(C)]
+pub struct TempusFreeBusy {
+    participants: Vec<Participant>,
+    /// Busy times that apply to everyone.
+    busy: Vec<HalfOpen<Timestamp>>,
+}
+
+impl TempusFreeBusy {
+    pub fn find(&self, search: HalfOpen<Timestamp>, min_length: SignedDuration) -> Result<Vec<HalfOpen<Timestamp>>, String> {
+        let mut free = normalize(vec![search]);
+        for participant in &self.participants {
+            let available = subtract_sets(&participant.working_windows(search)?, &normalize(participant.busy.clone()));
+            free = intersect_sets(&free, &available);
+        }
+        free = subtract_sets(&free, &normalize(self.busy.clone()));
+        free.retain(|slot| slot.start.duration_until(slot.end) >= min_length);
+        Ok(free)
+    }
+}
+
+#[repr(C)]
+pub struct TempusFreeBusySlots {
+    slots: Vec<HalfOpen<Timestamp>>
+}
+
+impl TempusFreeBusySlots {
+    pub fn stuff_into(self, pointer: *mut *mut TempusFreeBusySlots) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn freebusy_new() -> Box<TempusFreeBusy> {
+    Box::new(TempusFreeBusy{participants: Vec::new(), busy: Vec::new()})
+}
+
+/// Adds a participant and returns their index, for use with `freebusy_add_busy`. `workdays_mask` has a bit set
+/// for each working day, by the weekday's offset from Sunday (bit 0 is Sunday).
+#[no_mangle]
+pub extern "C" fn freebusy_add_participant(tfb: &mut TempusFreeBusy, ttz: &TempusTimeZone, work_start: &TempusTime, work_end: &TempusTime, workdays_mask: i8) -> c_longlong {
+    if workdays_mask & 0x7f == 0 {
+        set_last_error_message("a participant must work on at least one day of the week".to_string());
+        return -1
+    }
+    let mut workdays = [false; 7];
+    for (offset, workday) in workdays.iter_mut().enumerate() {
+        *workday = workdays_mask & (1 << offset) != 0;
+    }
+    tfb.participants.push(Participant{
+        tz: ttz.tz.clone(),
+        work_start: work_start.time,
+        work_end: work_end.time,
+        workdays,
+        busy: Vec::new(),
+    });
+    (tfb.participants.len() - 1) as c_longlong
+}
+
+/// Marks a participant as busy from `start` until `end`. A participant of -1 marks everyone as busy.
+#[no_mangle]
+pub extern "C" fn freebusy_add_busy(tfb: &mut TempusFreeBusy, participant: i64, start: &TempusZoned, end: &TempusZoned) -> c_longlong {
+    let range = match HalfOpen::new(start.zoned.timestamp(), end.zoned.timestamp()) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(range) => range
+    };
+    if participant == -1 {
+        tfb.busy.push(range);
+        return 0
+    }
+    match usize::try_from(participant).ok().and_then(|index| tfb.participants.get_mut(index)) {
+        None => {
+            set_last_error_message(format!("there is no participant {participant}"));
+            -2
+        }
+        Some(participant) => {
+            participant.busy.push(range);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn freebusy_find(tfb: &TempusFreeBusy, search_start: &TempusZoned, search_end: &TempusZoned, min_length: &TempusSignedDuration, out_slots: *mut *mut TempusFreeBusySlots) -> c_longlong {
+    let search = match HalfOpen::new(search_start.zoned.timestamp(), search_end.zoned.timestamp()) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(search) => search
+    };
+    match tfb.find(search, min_length.duration) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(slots) => {
+            let tslots = TempusFreeBusySlots{slots};
+            tslots.stuff_into(out_slots);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn freebusy_slots_len(tslots: &TempusFreeBusySlots) -> i64 {
+    tslots.slots.len() as i64
+}
+
+#[no_mangle]
+pub extern "C" fn freebusy_slots_get(tslots: &TempusFreeBusySlots, index: i64, out_range: *mut *mut TempusTimestampRange) -> c_longlong {
+    match usize::try_from(index).ok().and_then(|index| tslots.slots.get(index)) {
+        None => {
+            set_last_error_message(format!("slot index {index} is out of range"));
+            -1
+        }
+        Some(range) => {
+            let trange = TempusTimestampRange{range: *range};
+            trange.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn free_freebusy(tfb: Box<TempusFreeBusy>) -> c_longlong {
+    let raw = Box::into_raw(tfb);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_freebusy_slots(tslots: Box<TempusFreeBusySlots>) -> c_longlong {
+    let raw = Box::into_raw(tslots);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
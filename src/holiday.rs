@@ -0,0 +1,489 @@
+use std::collections::BTreeSet;
+use std::ffi::{c_char, c_longlong};
+use std::str::FromStr;
+use jiff::civil::{Date, Weekday};
+use crate::date::TempusDate;
+use crate::utils::{ahk_str_to_string, set_last_error_message, string_into_ahk_buff, AHKStringBuffer, AHKWstr};
+
+/// The date of Easter Sunday in the given year, by the Gregorian computus (the anonymous algorithm published by
+/// Meeus, also known as the Meeus/Jones/Butcher algorithm).
+pub fn easter(year: i16) -> Result<Date, String> {
+    if year < 1583 {
+        return Err(format!("the Gregorian computus is not defined for the year {year}"))
+    }
+    let y = year as i32;
+    let a = y % 19;
+    let b = y / 100;
+    let c = y % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    Date::new(year, month as i8, day as i8).map_err(|e| e.to_string())
+}
+
+/// When a holiday falls on a weekend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Observance {
+    /// The holiday is observed on the day it falls on, even on a weekend.
+    Actual,
+    /// Saturday is observed on the Friday before and Sunday on the Monday after (US style).
+    Nearest,
+    /// The holiday is observed on the next weekday that isn't already a holiday (UK style "substitute days").
+    Following,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleDate {
+    /// The same month and day every year.
+    Fixed { month: i8, day: i8 },
+    /// The nth weekday of a month. A negative `nth` counts from the end of the month (-1 is the last).
+    NthWeekday { month: i8, nth: i8, weekday: Weekday },
+    /// A number of days before or after Easter Sunday.
+    Easter { offset: i16 },
+}
+
+impl RuleDate {
+    /// The date in the given year, if there is one (there is no February 29 in a common year, and not every month
+    /// has a fifth Monday).
+    fn in_year(&self, year: i16) -> Option<Date> {
+        match *self {
+            RuleDate::Fixed{month, day} => Date::new(year, month, day).ok(),
+            RuleDate::NthWeekday{month, nth, weekday} => {
+                Date::new(year, month, 1).ok()?.nth_weekday_of_month(nth, weekday).ok()
+            }
+            RuleDate::Easter{offset} => easter(year).ok()?.checked_add(jiff::Span::new().days(offset)).ok(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HolidayRule {
+    pub name: String,
+    pub date: RuleDate,
+    pub observance: Observance,
+    /// The first and last years the rule applies to, if it doesn't apply to every year.
+    pub from_year: Option<i16>,
+    pub until_year: Option<i16>,
+}
+
+impl HolidayRule {
+    fn applies_to(&self, year: i16) -> bool {
+        self.from_year.unwrap_or(i16::MIN) <= year && year <= self.until_year.unwrap_or(i16::MAX)
+    }
+}
+
+fn is_weekend(date: Date) -> bool {
+    matches!(date.weekday(), Weekday::Saturday | Weekday::Sunday)
+}
+
+/// Accepts a weekday's English name or any abbreviation of it down to three letters, like `Thu` or `Thurs`.
+fn weekday_from_name(name: &str) -> Option<Weekday> {
+    let name = name.to_ascii_lowercase();
+    let weekdays = [
+        ("monday", Weekday::Monday), ("tuesday", Weekday::Tuesday), ("wednesday", Weekday::Wednesday),
+        ("thursday", Weekday::Thursday), ("friday", Weekday::Friday), ("saturday", Weekday::Saturday),
+        ("sunday", Weekday::Sunday),
+    ];
+    if name.len() < 3 {
+        return None
+    }
+    weekdays.iter().find(|(full, _)| full.starts_with(&name)).map(|(_, weekday)| *weekday)
+}
+
+fn parse_rule_date(s: &str) -> Result<RuleDate, String> {
+    let lower = s.to_ascii_lowercase();
+    if let Some(rest) = lower.strip_prefix("easter") {
+        let offset = if rest.is_empty() {
+            0
+        } else {
+            rest.strip_prefix('+').unwrap_or(rest).parse::<i16>().map_err(|_| format!("invalid Easter offset '{rest}'"))?
+        };
+        return Ok(RuleDate::Easter{offset})
+    }
+    if let Some((month_str, rest)) = s.split_once('/') {
+        let month: i8 = month_str.parse().map_err(|_| format!("invalid month '{month_str}'"))?;
+        if !(1..=12).contains(&month) {
+            return Err(format!("invalid month '{month_str}'"))
+        }
+        let (weekday_str, nth_str) = rest.split_once('#').ok_or_else(|| format!("expected a rule like 11/Thu#4, got '{s}'"))?;
+        let weekday = weekday_from_name(weekday_str).ok_or_else(|| format!("invalid weekday '{weekday_str}'"))?;
+        let nth: i8 = if nth_str.eq_ignore_ascii_case("last") {
+            -1
+        } else {
+            nth_str.parse().map_err(|_| format!("invalid weekday number '{nth_str}'"))?
+        };
+        if nth == 0 || !(-5..=5).contains(&nth) {
+            return Err(format!("invalid weekday number '{nth_str}' (expected 1 to 5, -1 to -5 or 'last')"))
+        }
+        return Ok(RuleDate::NthWeekday{month, nth, weekday})
+    }
+    if let Some((month_str, day_str)) = s.split_once('-') {
+        let month: i8 = month_str.parse().map_err(|_| format!("invalid month '{month_str}'"))?;
+        let day: i8 = day_str.parse().map_err(|_| format!("invalid day '{day_str}'"))?;
+        // 2000 is a leap year, so this accepts February 29
+        Date::new(2000, month, day).map_err(|e| e.to_string())?;
+        return Ok(RuleDate::Fixed{month, day})
+    }
+    Err(format!("expected a rule like 12-25, 11/Thu#4 or easter+1, got '{s}'"))
+}
+
+impl FromStr for HolidayRule {
+    type Err = String;
+
+    /// Parses a rule like `Thanksgiving = 11/Thu#4` or `Juneteenth = 06-19 nearest from 2021`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, definition) = s.split_once('=').ok_or_else(|| format!("expected 'name = rule', got '{s}'"))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(format!("missing holiday name in '{s}'"))
+        }
+        let mut tokens = definition.split_whitespace();
+        let date = parse_rule_date(tokens.next().ok_or_else(|| format!("missing rule for '{name}'"))?)?;
+        let mut rule = HolidayRule{name: name.to_string(), date, observance: Observance::Actual, from_year: None, until_year: None};
+        while let Some(token) = tokens.next() {
+            match token.to_ascii_lowercase().as_str() {
+                "nearest" => rule.observance = Observance::Nearest,
+                "following" => rule.observance = Observance::Following,
+                "from" | "until" => {
+                    let year_str = tokens.next().ok_or_else(|| format!("missing year after '{token}'"))?;
+                    let year: i16 = year_str.parse().map_err(|_| format!("invalid year '{year_str}'"))?;
+                    if token.eq_ignore_ascii_case("from") {
+                        rule.from_year = Some(year);
+                    } else {
+                        rule.until_year = Some(year);
+                    }
+                }
+                _ => return Err(format!("unexpected '{token}' in the rule for '{name}'"))
+            }
+        }
+        Ok(rule)
+    }
+}
+
+impl std::fmt::Display for HolidayRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = ", self.name)?;
+        match self.date {
+            RuleDate::Fixed{month, day} => write!(f, "{month:02}-{day:02}")?,
+            RuleDate::NthWeekday{month, nth, weekday} => {
+                let weekday = &format!("{weekday:?}")[..3];
+                if nth == -1 {
+                    write!(f, "{month:02}/{weekday}#last")?
+                } else {
+                    write!(f, "{month:02}/{weekday}#{nth}")?
+                }
+            }
+            RuleDate::Easter{offset: 0} => write!(f, "easter")?,
+            RuleDate::Easter{offset} => write!(f, "easter{offset:+}")?,
+        }
+        match self.observance {
+            Observance::Actual => {}
+            Observance::Nearest => write!(f, " nearest")?,
+            Observance::Following => write!(f, " following")?,
+        }
+        if let Some(year) = self.from_year {
+            write!(f, " from {year}")?;
+        }
+        if let Some(year) = self.until_year {
+            write!(f, " until {year}")?;
+        }
+        Ok(())
+    }
+}
+
+const RULES_US: &str = "
+New Year's Day = 01-01 nearest
+Birthday of Martin Luther King, Jr. = 01/Mon#3
+Washington's Birthday = 02/Mon#3
+Memorial Day = 05/Mon#last
+Juneteenth National Independence Day = 06-19 nearest from 2021
+Independence Day = 07-04 nearest
+Labor Day = 09/Mon#1
+Columbus Day = 10/Mon#2
+Veterans Day = 11-11 nearest
+Thanksgiving Day = 11/Thu#4
+Christmas Day = 12-25 nearest
+";
+
+const RULES_UK: &str = "
+New Year's Day = 01-01 following
+Good Friday = easter-2
+Easter Monday = easter+1
+Early May bank holiday = 05/Mon#1
+Spring bank holiday = 05/Mon#last
+Summer bank holiday = 08/Mon#last
+Christmas Day = 12-25 following
+Boxing Day = 12-26 following
+";
+
+const RULES_DE: &str = "
+Neujahr = 01-01
+Karfreitag = easter-2
+Ostermontag = easter+1
+Tag der Arbeit = 05-01
+Christi Himmelfahrt = easter+39
+Pfingstmontag = easter+50
+Tag der Deutschen Einheit = 10-03
+1. Weihnachtstag = 12-25
+2. Weihnachtstag = 12-26
+";
+
+/// A set of holiday rules, from which the holidays of any year can be computed.
+#[repr(C)]
+#[derive(Clone, Debug, Default)]
+pub struct TempusHolidayRules {
+    pub rules: Vec<HolidayRule>,
+}
+
+impl TempusHolidayRules {
+    /// Parses rules from text, one per line (or separated by `;`). Blank lines and lines starting with `#` are
+    /// ignored.
+    pub fn add_from_str(&mut self, s: &str) -> Result<(), String> {
+        let mut rules = Vec::new();
+        for (index, line) in s.split(['\n', ';']).enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            let rule = HolidayRule::from_str(line).map_err(|e| format!("invalid holiday rule {}: {e}", index + 1))?;
+            rules.push(rule);
+        }
+        self.rules.extend(rules);
+        Ok(())
+    }
+
+    pub fn builtin(name: &str) -> Result<Self, String> {
+        let text = match name.to_ascii_uppercase().as_str() {
+            "US" => RULES_US,
+            "UK" | "GB" => RULES_UK,
+            "DE" => RULES_DE,
+            _ => return Err(format!("there is no built-in holiday rule set named '{name}' (expected US, UK or DE)"))
+        };
+        let mut ret = TempusHolidayRules::default();
+        ret.add_from_str(text)?;
+        Ok(ret)
+    }
+
+    /// The holidays observed in the given year, sorted by date. A holiday can be observed in a different year than
+    /// the one it falls in (a Saturday, January 1 observed on Friday, December 31), so the neighbouring years are
+    /// resolved too.
+    pub fn holidays(&self, year: i16) -> Vec<(Date, String)> {
+        let mut ret: Vec<(Date, String)> = Vec::new();
+        for y in [year.saturating_sub(1), year, year.saturating_add(1)] {
+            let mut actual: Vec<(Date, &HolidayRule)> = self.rules.iter()
+                .filter(|rule| rule.applies_to(y))
+                .filter_map(|rule| rule.date.in_year(y).map(|date| (date, rule)))
+                .collect();
+            actual.sort_by_key(|(date, _)| *date);
+            // holidays on a weekday stay put, so substitute days have to go around them
+            let mut taken: BTreeSet<Date> = actual.iter().map(|(date, _)| *date).filter(|date| !is_weekend(*date)).collect();
+            for (date, rule) in actual {
+                let observed = if !is_weekend(date) {
+                    date
+                } else {
+                    match rule.observance {
+                        Observance::Actual => date,
+                        Observance::Nearest => match date.weekday() {
+                            Weekday::Saturday => date.yesterday().unwrap_or(date),
+                            _ => date.tomorrow().unwrap_or(date),
+                        },
+                        Observance::Following => {
+                            let mut observed = date;
+                            while is_weekend(observed) || taken.contains(&observed) {
+                                observed = match observed.tomorrow() {
+                                    Ok(next) => next,
+                                    Err(_) => break,
+                                };
+                            }
+                            taken.insert(observed);
+                            observed
+                        }
+                    }
+                };
+                if observed.year() == year {
+                    ret.push((observed, rule.name.clone()));
+                }
+            }
+        }
+        ret.sort_by_key(|(date, _)| *date);
+        ret
+    }
+
+    pub fn is_holiday(&self, date: Date) -> bool {
+        self.holidays(date.year()).iter().any(|(holiday, _)| *holiday == date)
+    }
+}
+
+impl std::fmt::Display for TempusHolidayRules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rule in &self.rules {
+            writeln!(f, "{rule}")?;
+        }
+        Ok(())
+    }
+}
+
+#[repr(C)]
+pub struct TempusHolidayList {
+    holidays: Vec<(Date, String)>
+}
+
+impl TempusHolidayList {
+    pub fn stuff_into(self, pointer: *mut *mut TempusHolidayList) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+impl TempusHolidayRules {
+    pub fn stuff_into(self, pointer: *mut *mut TempusHolidayRules) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn holiday_rules_new() -> Box<TempusHolidayRules> {
+    Box::new(TempusHolidayRules::default())
+}
+
+#[no_mangle]
+pub extern "C" fn holiday_rules_builtin(ahk_name: AHKWstr, out_rules: *mut *mut TempusHolidayRules) -> c_longlong {
+    match ahk_str_to_string(ahk_name) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(name) => {
+            match TempusHolidayRules::builtin(&name) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(trules) => {
+                    trules.stuff_into(out_rules);
+                    0
+                }
+            }
+        }
+    }
+}
+
+/// Adds the rules in the given text. Nothing is added if any of the rules is invalid.
+#[no_mangle]
+pub extern "C" fn holiday_rules_add(trules: &mut TempusHolidayRules, ahk_string: AHKWstr) -> c_longlong {
+    match ahk_str_to_string(ahk_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(s) => {
+            match trules.add_from_str(&s) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(()) => 0
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn holiday_rules_len(trules: &TempusHolidayRules) -> i64 {
+    trules.rules.len() as i64
+}
+
+#[no_mangle]
+pub extern "C" fn holiday_rules_string_length(trules: &TempusHolidayRules) -> usize {
+    trules.to_string().len()
+}
+
+#[no_mangle]
+pub extern "C" fn holiday_rules_to_string(trules: &TempusHolidayRules, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(trules.to_string(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn holiday_rules_is_holiday(trules: &TempusHolidayRules, tdate: &TempusDate) -> c_char {
+    trules.is_holiday(tdate.date) as i8
+}
+
+#[no_mangle]
+pub extern "C" fn holiday_rules_holidays(trules: &TempusHolidayRules, year: i16) -> Box<TempusHolidayList> {
+    Box::new(TempusHolidayList{holidays: trules.holidays(year)})
+}
+
+#[no_mangle]
+pub extern "C" fn holiday_list_len(tlist: &TempusHolidayList) -> i64 {
+    tlist.holidays.len() as i64
+}
+
+#[no_mangle]
+pub extern "C" fn holiday_list_date(tlist: &TempusHolidayList, index: i64, out_date: *mut *mut TempusDate) -> c_longlong {
+    match usize::try_from(index).ok().and_then(|index| tlist.holidays.get(index)) {
+        None => {
+            set_last_error_message(format!("holiday index {index} is out of range"));
+            -1
+        }
+        Some((date, _)) => {
+            let td = TempusDate{date: *date};
+            td.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+/// The length of the holiday's name, or -1 if the index is out of range.
+#[no_mangle]
+pub extern "C" fn holiday_list_name_length(tlist: &TempusHolidayList, index: i64) -> i64 {
+    match usize::try_from(index).ok().and_then(|index| tlist.holidays.get(index)) {
+        None => -1,
+        Some((_, name)) => name.len() as i64
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn holiday_list_name(tlist: &TempusHolidayList, index: i64, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    match usize::try_from(index).ok().and_then(|index| tlist.holidays.get(index)) {
+        None => {
+            set_last_error_message(format!("holiday index {index} is out of range"));
+            -1
+        }
+        Some((_, name)) => {
+            string_into_ahk_buff(name.clone(), out_buff, buff_len);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn free_holiday_rules(trules: Box<TempusHolidayRules>) -> c_longlong {
+    let raw = Box::into_raw(trules);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_holiday_list(tlist: Box<TempusHolidayList>) -> c_longlong {
+    let raw = Box::into_raw(tlist);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
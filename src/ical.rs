@@ -0,0 +1,500 @@
+use std::ffi::{c_char, c_longlong, c_void};
+use std::str::FromStr;
+use jiff::civil::{Date, DateTime};
+use jiff::tz::TimeZone;
+use jiff::{Span, Zoned};
+use crate::date::TempusDate;
+use crate::datetime::TempusDateTime;
+use crate::span::TempusSpan;
+use crate::utils::{ahk_str_to_string, set_last_error_message, string_into_ahk_buff, AHKStringBuffer, AHKWstr};
+use crate::zoned::TempusZoned;
+
+/// Properties whose values are durations unless a VALUE parameter says otherwise.
+const DURATION_PROPERTIES: [&str; 3] = ["DURATION", "TRIGGER", "REFRESH-INTERVAL"];
+
+/// Content lines longer than this many octets are folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+#[derive(Clone, Debug)]
+enum IcalValue {
+    Zoned(Zoned),
+    /// A floating local time, which happens at the same wall clock time in any time zone.
+    DateTime(DateTime),
+    Date(Date),
+    Duration(Span),
+}
+
+impl IcalValue {
+    /// 1 = Date, 2 = DateTime (floating), 3 = Zoned, 4 = Span (duration)
+    fn kind(&self) -> i8 {
+        match self {
+            IcalValue::Date(_) => 1,
+            IcalValue::DateTime(_) => 2,
+            IcalValue::Zoned(_) => 3,
+            IcalValue::Duration(_) => 4,
+        }
+    }
+
+    /// Stores a new handle of the matching Tempus type in `out` and returns its kind.
+    fn stuff_into(&self, out: *mut *mut c_void) -> c_char {
+        let handle = match self {
+            IcalValue::Date(date) => Box::into_raw(Box::new(TempusDate{date: *date})) as *mut c_void,
+            IcalValue::DateTime(datetime) => Box::into_raw(Box::new(TempusDateTime{datetime: *datetime})) as *mut c_void,
+            IcalValue::Zoned(zoned) => Box::into_raw(Box::new(TempusZoned{zoned: zoned.clone()})) as *mut c_void,
+            IcalValue::Duration(span) => Box::into_raw(Box::new(TempusSpan{span: *span})) as *mut c_void,
+        };
+        unsafe {
+            *out = handle;
+        }
+        self.kind()
+    }
+}
+
+fn digits(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn parse_date(s: &str) -> Result<Date, String> {
+    if !digits(s, 8) {
+        return Err(format!("invalid iCalendar date '{s}' (expected a date like 20250310)"))
+    }
+    Date::new(s[0..4].parse().unwrap(), s[4..6].parse().unwrap(), s[6..8].parse().unwrap()).map_err(|e| e.to_string())
+}
+
+/// Parses a date-time like `20250310T090000`, and whether it ended in `Z` (UTC).
+fn parse_datetime(s: &str) -> Result<(DateTime, bool), String> {
+    let err = || format!("invalid iCalendar date-time '{s}' (expected a date-time like 20250310T090000 or 20250310T090000Z)");
+    let (s, utc) = match s.strip_suffix('Z') {
+        Some(s) => (s, true),
+        None => (s, false),
+    };
+    let (date, time) = s.split_once('T').ok_or_else(err)?;
+    if !digits(time, 6) {
+        return Err(err())
+    }
+    let date = parse_date(date).map_err(|_| err())?;
+    let (hour, minute, second) = (time[0..2].parse().unwrap(), time[2..4].parse().unwrap(), time[4..6].parse().unwrap());
+    let datetime = DateTime::new(date.year(), date.month(), date.day(), hour, minute, second, 0).map_err(|e| e.to_string())?;
+    Ok((datetime, utc))
+}
+
+/// Parses an RFC 5545 duration like `P1W`, `-PT15M` or `P1DT12H`.
+fn parse_duration(s: &str) -> Result<Span, String> {
+    let err = || format!("invalid iCalendar duration '{s}' (expected a duration like P1DT12H or -PT15M)");
+    let (negative, rest) = match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let mut rest = rest.strip_prefix('P').ok_or_else(err)?;
+    let mut span = Span::new();
+    let mut in_time = false;
+    // the units must come in this order, each at most once
+    let mut units = "WDHMS";
+    let mut any = false;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('T') {
+            if in_time {
+                return Err(err())
+            }
+            in_time = true;
+            units = "HMS";
+            rest = after;
+            continue
+        }
+        let number_len = rest.bytes().take_while(|b| b.is_ascii_digit()).count();
+        if number_len == 0 || number_len == rest.len() {
+            return Err(err())
+        }
+        let number: i64 = rest[..number_len].parse().map_err(|_| err())?;
+        let unit = rest[number_len..].chars().next().ok_or_else(err)?;
+        let position = units.find(unit).ok_or_else(err)?;
+        if in_time != matches!(unit, 'H' | 'M' | 'S') {
+            return Err(err())
+        }
+        units = &units[position + 1..];
+        span = match unit {
+            'W' => span.try_weeks(number),
+            'D' => span.try_days(number),
+            'H' => span.try_hours(number),
+            'M' => span.try_minutes(number),
+            _ => span.try_seconds(number),
+        }.map_err(|e| e.to_string())?;
+        rest = &rest[number_len + 1..];
+        any = true;
+    }
+    if !any {
+        return Err(err())
+    }
+    Ok(if negative { span.negate() } else { span })
+}
+
+fn format_duration(span: &Span) -> String {
+    let mut ret = if span.is_negative() { "-P".to_string() } else { "P".to_string() };
+    let span = span.abs();
+    let (weeks, days) = (span.get_weeks() as i64, span.get_days() as i64);
+    let time = [(span.get_hours() as i64, 'H'), (span.get_minutes(), 'M'), (span.get_seconds(), 'S')];
+    if weeks != 0 && days == 0 && time.iter().all(|(n, _)| *n == 0) {
+        return format!("{ret}{weeks}W")
+    }
+    if weeks * 7 + days != 0 {
+        ret.push_str(&format!("{}D", weeks * 7 + days));
+    }
+    // the time units have to be consecutive (PT1H0M5S rather than PT1H5S)
+    let first = time.iter().position(|(n, _)| *n != 0);
+    let last = time.iter().rposition(|(n, _)| *n != 0);
+    if let (Some(first), Some(last)) = (first, last) {
+        ret.push('T');
+        for (n, unit) in &time[first..=last] {
+            ret.push_str(&format!("{n}{unit}"));
+        }
+    }
+    if ret.ends_with('P') {
+        ret.push_str("T0S");
+    }
+    ret
+}
+
+fn format_datetime(datetime: DateTime) -> String {
+    datetime.strftime("%Y%m%dT%H%M%S").to_string()
+}
+
+/// The IANA name to use for TZID, or `None` if the zoned datetime should be written in UTC.
+fn tzid_of(zoned: &Zoned) -> Option<&str> {
+    zoned.time_zone().iana_name().filter(|name| !matches!(*name, "UTC" | "Etc/UTC"))
+}
+
+/// Splits on `delimiter`, except inside double quotes.
+fn split_unquoted(s: &str, delimiter: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == delimiter && !quoted {
+            parts.push(&s[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// An iCalendar (RFC 5545) property with date, date-time or duration values, like
+/// `DTSTART;TZID=Europe/Berlin:20250310T090000`.
+#[derive(Clone, Debug)]
+pub struct IcalProperty {
+    name: String,
+    /// Parameters other than VALUE and TZID, which are written from the values.
+    params: Vec<(String, String)>,
+    values: Vec<IcalValue>,
+}
+
+impl IcalProperty {
+    pub fn new(name: &str) -> Result<Self, String> {
+        if !valid_name(name) {
+            return Err(format!("invalid iCalendar property name '{name}'"))
+        }
+        Ok(IcalProperty{name: name.to_ascii_uppercase(), params: Vec::new(), values: Vec::new()})
+    }
+
+    /// Adds a value. All the values of a property must be of the same kind, and zoned datetimes must share a time
+    /// zone. Fractional seconds are dropped, since iCalendar doesn't have them.
+    fn add(&mut self, value: IcalValue) -> Result<(), String> {
+        let value = match value {
+            IcalValue::Zoned(zoned) => {
+                let zoned = zoned.with().subsec_nanosecond(0).build().map_err(|e| e.to_string())?;
+                IcalValue::Zoned(if tzid_of(&zoned).is_none() { zoned.with_time_zone(TimeZone::UTC) } else { zoned })
+            }
+            IcalValue::DateTime(datetime) => IcalValue::DateTime(datetime.with().subsec_nanosecond(0).build().map_err(|e| e.to_string())?),
+            IcalValue::Duration(span) => {
+                if span.get_years() != 0 || span.get_months() != 0 {
+                    return Err("iCalendar durations can't have years or months".to_string())
+                }
+                IcalValue::Duration(span.milliseconds(0).microseconds(0).nanoseconds(0))
+            }
+            value => value,
+        };
+        let year = match &value {
+            IcalValue::Zoned(zoned) => Some(zoned.year()),
+            IcalValue::DateTime(datetime) => Some(datetime.year()),
+            IcalValue::Date(date) => Some(date.year()),
+            IcalValue::Duration(_) => None,
+        };
+        if year.is_some_and(|year| year < 0) {
+            return Err("iCalendar dates must be in year 0 or later".to_string())
+        }
+        if let Some(first) = self.values.first() {
+            if first.kind() != value.kind() {
+                return Err("all values of an iCalendar property must be of the same kind".to_string())
+            }
+            if let (IcalValue::Zoned(first), IcalValue::Zoned(zoned)) = (first, &value) {
+                if tzid_of(first) != tzid_of(zoned) {
+                    return Err("all values of an iCalendar property must be in the same time zone".to_string())
+                }
+            }
+        }
+        self.values.push(value);
+        Ok(())
+    }
+}
+
+impl FromStr for IcalProperty {
+    type Err = String;
+
+    /// Parses a content line. Folded lines are unfolded first.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = s.replace("\r\n ", "").replace("\r\n\t", "").replace("\n ", "").replace("\n\t", "");
+        let line = line.trim_end_matches(['\r', '\n']);
+        let colon = line.char_indices()
+            .scan(false, |quoted, (i, c)| {
+                if c == '"' {
+                    *quoted = !*quoted;
+                }
+                Some((i, c == ':' && !*quoted))
+            })
+            .find(|(_, is_colon)| *is_colon)
+            .map(|(i, _)| i)
+            .ok_or_else(|| format!("invalid iCalendar content line '{line}' (expected NAME:VALUE)"))?;
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+        let mut head = split_unquoted(head, ';').into_iter();
+        let mut property = IcalProperty::new(head.next().unwrap_or_default())?;
+        let mut value_type = None;
+        let mut tzid = None;
+        for param in head {
+            let (key, param_value) = param.split_once('=').ok_or_else(|| format!("invalid iCalendar parameter '{param}'"))?;
+            let param_value = param_value.trim_matches('"');
+            match key.to_ascii_uppercase().as_str() {
+                "VALUE" => value_type = Some(param_value.to_ascii_uppercase()),
+                // a leading slash marks a globally unique id, which is looked up by name all the same
+                "TZID" => tzid = Some(TimeZone::get(param_value.trim_start_matches('/')).map_err(|e| e.to_string())?),
+                key => {
+                    if !valid_name(key) {
+                        return Err(format!("invalid iCalendar parameter name '{key}'"))
+                    }
+                    property.params.push((key.to_string(), param_value.to_string()));
+                }
+            }
+        }
+        let value_type = match value_type.as_deref() {
+            None if DURATION_PROPERTIES.contains(&property.name.as_str()) => Some("DURATION"),
+            None => None,
+            Some(value_type @ ("DATE" | "DATE-TIME" | "DURATION")) => Some(value_type),
+            Some(other) => return Err(format!("unsupported iCalendar VALUE type '{other}'")),
+        };
+        for item in value.split(',') {
+            let value_type = value_type.unwrap_or_else(|| {
+                if item.starts_with(['P', '+', '-']) {
+                    "DURATION"
+                } else if item.contains('T') {
+                    "DATE-TIME"
+                } else {
+                    "DATE"
+                }
+            });
+            let value = match value_type {
+                "DATE" => IcalValue::Date(parse_date(item)?),
+                "DURATION" => IcalValue::Duration(parse_duration(item)?),
+                _ => match (parse_datetime(item)?, &tzid) {
+                    ((datetime, true), None) => IcalValue::Zoned(datetime.to_zoned(TimeZone::UTC).map_err(|e| e.to_string())?),
+                    ((_, true), Some(_)) => return Err(format!("the UTC date-time '{item}' can't have a TZID")),
+                    ((datetime, false), Some(tz)) => IcalValue::Zoned(datetime.to_zoned(tz.clone()).map_err(|e| e.to_string())?),
+                    ((datetime, false), None) => IcalValue::DateTime(datetime),
+                },
+            };
+            property.add(value)?;
+        }
+        Ok(property)
+    }
+}
+
+impl std::fmt::Display for IcalProperty {
+    /// Writes a content line, folded at 75 octets.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut line = self.name.clone();
+        for (key, value) in &self.params {
+            if value.contains([':', ';', ',']) {
+                line.push_str(&format!(";{key}=\"{value}\""));
+            } else {
+                line.push_str(&format!(";{key}={value}"));
+            }
+        }
+        match self.values.first() {
+            Some(IcalValue::Date(_)) => line.push_str(";VALUE=DATE"),
+            Some(IcalValue::Duration(_)) if !DURATION_PROPERTIES.contains(&self.name.as_str()) => line.push_str(";VALUE=DURATION"),
+            Some(IcalValue::Zoned(zoned)) => {
+                if let Some(tzid) = tzid_of(zoned) {
+                    line.push_str(&format!(";TZID={tzid}"));
+                }
+            }
+            _ => {}
+        }
+        let values: Vec<String> = self.values.iter().map(|value| match value {
+            IcalValue::Zoned(zoned) => match tzid_of(zoned) {
+                Some(_) => format_datetime(zoned.datetime()),
+                None => format!("{}Z", format_datetime(zoned.datetime())),
+            },
+            IcalValue::DateTime(datetime) => format_datetime(*datetime),
+            IcalValue::Date(date) => date.strftime("%Y%m%d").to_string(),
+            IcalValue::Duration(span) => format_duration(span),
+        }).collect();
+        line.push(':');
+        line.push_str(&values.join(","));
+        let mut octets = 0;
+        for c in line.chars() {
+            // continuation lines start with a space, which counts towards their length
+            if octets + c.len_utf8() > MAX_LINE_OCTETS {
+                write!(f, "\r\n ")?;
+                octets = 1;
+            }
+            write!(f, "{c}")?;
+            octets += c.len_utf8();
+        }
+        Ok(())
+    }
+}
+
+#[repr(C)]
+pub struct TempusIcalProperty {
+    property: IcalProperty,
+}
+
+impl TempusIcalProperty {
+    pub fn stuff_into(self, pointer: *mut *mut TempusIcalProperty) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ical_property_parse(ahk_string: AHKWstr, out_property: *mut *mut TempusIcalProperty) -> c_longlong {
+    match ahk_str_to_string(ahk_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(s) => {
+            match IcalProperty::from_str(&s) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(property) => {
+                    TempusIcalProperty{property}.stuff_into(out_property);
+                    0
+                }
+            }
+        }
+    }
+}
+
+/// A new property with no values.
+#[no_mangle]
+pub extern "C" fn ical_property_new(ahk_name: AHKWstr, out_property: *mut *mut TempusIcalProperty) -> c_longlong {
+    match ahk_str_to_string(ahk_name) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(name) => {
+            match IcalProperty::new(&name) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(property) => {
+                    TempusIcalProperty{property}.stuff_into(out_property);
+                    0
+                }
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ical_property_name_length(tprop: &TempusIcalProperty) -> usize {
+    tprop.property.name.len()
+}
+
+#[no_mangle]
+pub extern "C" fn ical_property_name(tprop: &TempusIcalProperty, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(tprop.property.name.clone(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn ical_property_len(tprop: &TempusIcalProperty) -> i64 {
+    tprop.property.values.len() as i64
+}
+
+/// Stores a new Date, DateTime, Zoned or Span handle for the value at the index and returns which
+/// (1 = Date, 2 = DateTime, 3 = Zoned, 4 = Span), or -1 if the index is out of range.
+#[no_mangle]
+pub extern "C" fn ical_property_value(tprop: &TempusIcalProperty, index: i64, out_handle: *mut *mut c_void) -> c_char {
+    match usize::try_from(index).ok().and_then(|index| tprop.property.values.get(index)) {
+        None => {
+            set_last_error_message(format!("value index {index} is out of range"));
+            -1
+        }
+        Some(value) => value.stuff_into(out_handle)
+    }
+}
+
+fn add_value(tprop: &mut TempusIcalProperty, value: IcalValue) -> c_longlong {
+    match tprop.property.add(value) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(()) => 0
+    }
+}
+
+/// Adds a zoned datetime, written with a TZID (or in UTC, for zoned datetimes without an IANA time zone).
+#[no_mangle]
+pub extern "C" fn ical_property_add_zoned(tprop: &mut TempusIcalProperty, tzoned: &TempusZoned) -> c_longlong {
+    add_value(tprop, IcalValue::Zoned(tzoned.zoned.clone()))
+}
+
+/// Adds a floating datetime.
+#[no_mangle]
+pub extern "C" fn ical_property_add_datetime(tprop: &mut TempusIcalProperty, tdt: &TempusDateTime) -> c_longlong {
+    add_value(tprop, IcalValue::DateTime(tdt.datetime))
+}
+
+#[no_mangle]
+pub extern "C" fn ical_property_add_date(tprop: &mut TempusIcalProperty, tdate: &TempusDate) -> c_longlong {
+    add_value(tprop, IcalValue::Date(tdate.date))
+}
+
+#[no_mangle]
+pub extern "C" fn ical_property_add_span(tprop: &mut TempusIcalProperty, tspan: &TempusSpan) -> c_longlong {
+    add_value(tprop, IcalValue::Duration(tspan.span))
+}
+
+#[no_mangle]
+pub extern "C" fn ical_property_string_length(tprop: &TempusIcalProperty) -> usize {
+    tprop.property.to_string().len()
+}
+
+#[no_mangle]
+pub extern "C" fn ical_property_to_string(tprop: &TempusIcalProperty, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(tprop.property.to_string(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_ical_property(tprop: Box<TempusIcalProperty>) -> c_longlong {
+    let raw = Box::into_raw(tprop);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
@@ -0,0 +1,374 @@
+use std::ffi::{c_char, c_longlong, c_void};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use jiff::civil::{Date, DateTime};
+use jiff::fmt::temporal::Pieces;
+use jiff::tz::{Offset, TimeZone};
+use jiff::{Span, Timestamp, Unit, Zoned};
+use crate::date::TempusDate;
+use crate::datetime::TempusDateTime;
+use crate::span::TempusSpan;
+use crate::timestamp::TempusTimestamp;
+use crate::utils::{ahk_str_to_string, set_last_error_message, string_into_ahk_buff, AHKStringBuffer, AHKWstr};
+use crate::zoned::TempusZoned;
+
+/// One end of an ISO 8601 interval. Timestamps keep the offset they were written with (as a zoned datetime in a
+/// fixed offset time zone) so that calendar units can be added to them and they format the same way.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IntervalPoint {
+    Date(Date),
+    DateTime(DateTime),
+    Timestamp(Zoned),
+    Zoned(Zoned),
+}
+
+impl IntervalPoint {
+    /// The kind of point, as returned to AutoHotkey: 1 = Date, 2 = DateTime, 3 = Timestamp, 4 = Zoned.
+    fn kind(&self) -> i8 {
+        match self {
+            IntervalPoint::Date(_) => 1,
+            IntervalPoint::DateTime(_) => 2,
+            IntervalPoint::Timestamp(_) => 3,
+            IntervalPoint::Zoned(_) => 4,
+        }
+    }
+
+    fn checked_add(&self, span: Span) -> Result<IntervalPoint, String> {
+        let point = match self {
+            IntervalPoint::Date(date) => IntervalPoint::Date(date.checked_add(span).map_err(|e| e.to_string())?),
+            IntervalPoint::DateTime(datetime) => IntervalPoint::DateTime(datetime.checked_add(span).map_err(|e| e.to_string())?),
+            IntervalPoint::Timestamp(zoned) => IntervalPoint::Timestamp(zoned.checked_add(span).map_err(|e| e.to_string())?),
+            IntervalPoint::Zoned(zoned) => IntervalPoint::Zoned(zoned.checked_add(span).map_err(|e| e.to_string())?),
+        };
+        Ok(point)
+    }
+
+    fn until(&self, other: &IntervalPoint) -> Result<Span, String> {
+        let span = match (self, other) {
+            (IntervalPoint::Date(start), IntervalPoint::Date(end)) => start.until((Unit::Year, *end)),
+            (IntervalPoint::DateTime(start), IntervalPoint::DateTime(end)) => start.until((Unit::Year, *end)),
+            (IntervalPoint::Timestamp(start), IntervalPoint::Timestamp(end)) => start.until((Unit::Year, end)),
+            (IntervalPoint::Zoned(start), IntervalPoint::Zoned(end)) => start.until((Unit::Year, end)),
+            _ => return Err("the start and end of an interval must be the same kind of value".to_string())
+        };
+        span.map_err(|e| e.to_string())
+    }
+
+    /// Stores a new handle of the matching Tempus type in `out` and returns its kind.
+    fn stuff_into(&self, out: *mut *mut c_void) -> c_char {
+        let handle = match self {
+            IntervalPoint::Date(date) => Box::into_raw(Box::new(TempusDate{date: *date})) as *mut c_void,
+            IntervalPoint::DateTime(datetime) => Box::into_raw(Box::new(TempusDateTime{datetime: *datetime})) as *mut c_void,
+            IntervalPoint::Timestamp(zoned) => Box::into_raw(Box::new(TempusTimestamp{ts: zoned.timestamp()})) as *mut c_void,
+            IntervalPoint::Zoned(zoned) => Box::into_raw(Box::new(TempusZoned{zoned: zoned.clone()})) as *mut c_void,
+        };
+        unsafe {
+            *out = handle;
+        }
+        self.kind()
+    }
+}
+
+impl FromStr for IntervalPoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pieces = Pieces::parse(s).map_err(|e| e.to_string())?;
+        if pieces.time_zone_annotation().is_some() {
+            let zoned: Zoned = s.parse().map_err(|e: jiff::Error| e.to_string())?;
+            return Ok(IntervalPoint::Zoned(zoned))
+        }
+        if let Some(offset) = pieces.to_numeric_offset() {
+            let ts: Timestamp = s.parse().map_err(|e: jiff::Error| e.to_string())?;
+            return Ok(IntervalPoint::Timestamp(ts.to_zoned(TimeZone::fixed(offset))))
+        }
+        match pieces.time() {
+            Some(time) => Ok(IntervalPoint::DateTime(pieces.date().to_datetime(time))),
+            None => Ok(IntervalPoint::Date(pieces.date())),
+        }
+    }
+}
+
+impl Display for IntervalPoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntervalPoint::Date(date) => write!(f, "{date}"),
+            IntervalPoint::DateTime(datetime) => write!(f, "{datetime}"),
+            IntervalPoint::Timestamp(zoned) if zoned.offset() == Offset::UTC => write!(f, "{}", zoned.timestamp()),
+            IntervalPoint::Timestamp(zoned) => write!(f, "{}", zoned.timestamp().display_with_offset(zoned.offset())),
+            IntervalPoint::Zoned(zoned) => write!(f, "{zoned}"),
+        }
+    }
+}
+
+/// Which of the three ISO 8601 interval forms an interval was written in, so it can be formatted the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntervalForm {
+    StartEnd,
+    StartDuration,
+    DurationEnd,
+}
+
+/// How many times a repeating interval repeats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Repeat {
+    Once,
+    Times(i64),
+    Unbounded,
+}
+
+/// An ISO 8601 time interval (`start/end`, `start/duration` or `duration/end`), optionally repeating (`Rn/...`).
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct TempusInterval {
+    pub start: IntervalPoint,
+    pub end: IntervalPoint,
+    pub span: Span,
+    pub form: IntervalForm,
+    pub repeat: Repeat,
+}
+
+impl TempusInterval {
+    pub fn stuff_into(self, pointer: *mut *mut TempusInterval) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+/// Splits an interval on `/`, except inside a bracketed time zone annotation like `[America/New_York]`.
+fn split_outside_brackets(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut part_start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '/' if depth == 0 => {
+                parts.push(&s[part_start..i]);
+                part_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[part_start..]);
+    parts
+}
+
+impl FromStr for TempusInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = split_outside_brackets(s);
+        let repeat = match parts.first() {
+            Some(first) if first.starts_with('R') => {
+                let count = &first[1..];
+                let repeat = match count {
+                    "" | "-1" => Repeat::Unbounded,
+                    _ => {
+                        let times: i64 = count.parse().map_err(|_| format!("invalid number of repetitions in '{s}'"))?;
+                        if times < 0 {
+                            return Err(format!("invalid number of repetitions in '{s}'"))
+                        }
+                        Repeat::Times(times)
+                    }
+                };
+                parts.remove(0);
+                repeat
+            }
+            _ => Repeat::Once
+        };
+        if parts.len() != 2 {
+            return Err(format!("failed to parse '{s}' as an ISO 8601 interval (expected start/end, start/duration or duration/end)"))
+        }
+        let is_duration = |part: &str| part.trim_start_matches(['-', '+']).starts_with(['P', 'p']);
+        let (start, end, span, form) = match (is_duration(parts[0]), is_duration(parts[1])) {
+            (false, false) => {
+                let start: IntervalPoint = parts[0].parse()?;
+                let end: IntervalPoint = parts[1].parse()?;
+                let span = start.until(&end)?;
+                (start, end, span, IntervalForm::StartEnd)
+            }
+            (false, true) => {
+                let start: IntervalPoint = parts[0].parse()?;
+                let span: Span = parts[1].parse().map_err(|e: jiff::Error| e.to_string())?;
+                let end = start.checked_add(span)?;
+                (start, end, span, IntervalForm::StartDuration)
+            }
+            (true, false) => {
+                let span: Span = parts[0].parse().map_err(|e: jiff::Error| e.to_string())?;
+                let end: IntervalPoint = parts[1].parse()?;
+                let start = end.checked_add(span.negate())?;
+                (start, end, span, IntervalForm::DurationEnd)
+            }
+            (true, true) => {
+                return Err(format!("interval '{s}' has no start or end"))
+            }
+        };
+        if span.is_negative() {
+            return Err(format!("the end of interval '{s}' is before its start"))
+        }
+        Ok(TempusInterval{start, end, span, form, repeat})
+    }
+}
+
+impl Display for TempusInterval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.repeat {
+            Repeat::Once => {}
+            Repeat::Times(times) => write!(f, "R{times}/")?,
+            Repeat::Unbounded => write!(f, "R/")?,
+        }
+        match self.form {
+            IntervalForm::StartEnd => write!(f, "{}/{}", self.start, self.end),
+            IntervalForm::StartDuration => write!(f, "{}/{}", self.start, self.span),
+            IntervalForm::DurationEnd => write!(f, "{}/{}", self.span, self.end),
+        }
+    }
+}
+
+/// The occurrences of a repeating interval. Intervals written with a start repeat forwards from it; intervals
+/// written as `duration/end` repeat backwards from the end, so the series yields them latest first.
+#[repr(C)]
+pub struct TempusIntervalSeries {
+    interval: TempusInterval,
+    index: i64,
+}
+
+impl TempusIntervalSeries {
+    pub fn stuff_into(self, pointer: *mut *mut TempusIntervalSeries) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+impl Iterator for TempusIntervalSeries {
+    type Item = TempusInterval;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let count = match self.interval.repeat {
+            Repeat::Once => 1,
+            Repeat::Times(times) => times,
+            Repeat::Unbounded => i64::MAX,
+        };
+        if self.index >= count {
+            return None
+        }
+        let interval = &self.interval;
+        let (start, end) = if interval.form == IntervalForm::DurationEnd {
+            let end = interval.end.checked_add(interval.span.checked_mul(-self.index).ok()?).ok()?;
+            (end.checked_add(interval.span.negate()).ok()?, end)
+        } else {
+            let start = interval.start.checked_add(interval.span.checked_mul(self.index).ok()?).ok()?;
+            (start.clone(), start.checked_add(interval.span).ok()?)
+        };
+        self.index += 1;
+        Some(TempusInterval{start, end, span: interval.span, form: interval.form, repeat: Repeat::Once})
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn interval_parse(ahk_string: AHKWstr, out_interval: *mut *mut TempusInterval) -> c_longlong {
+    match ahk_str_to_string(ahk_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(s) => {
+            match TempusInterval::from_str(&s) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(interval) => {
+                    interval.stuff_into(out_interval);
+                    0
+                }
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn interval_string_length(tiv: &TempusInterval) -> usize {
+    tiv.to_string().len()
+}
+
+#[no_mangle]
+pub extern "C" fn interval_to_string(tiv: &TempusInterval, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(tiv.to_string(), out_buff, buff_len);
+    0
+}
+
+/// Stores a new Date, DateTime, Timestamp or Zoned handle for the start of the interval and returns which
+/// (1 = Date, 2 = DateTime, 3 = Timestamp, 4 = Zoned).
+#[no_mangle]
+pub extern "C" fn interval_start(tiv: &TempusInterval, out_handle: *mut *mut c_void) -> c_char {
+    tiv.start.stuff_into(out_handle)
+}
+
+/// Like `interval_start`, for the end of the interval.
+#[no_mangle]
+pub extern "C" fn interval_end(tiv: &TempusInterval, out_handle: *mut *mut c_void) -> c_char {
+    tiv.end.stuff_into(out_handle)
+}
+
+#[no_mangle]
+pub extern "C" fn interval_span(tiv: &TempusInterval) -> Box<TempusSpan> {
+    Box::new(TempusSpan{span: tiv.span})
+}
+
+#[no_mangle]
+pub extern "C" fn interval_is_repeating(tiv: &TempusInterval) -> c_char {
+    (tiv.repeat != Repeat::Once) as i8
+}
+
+/// The number of repetitions, or -1 if the interval repeats without bound. Non-repeating intervals return 1.
+#[no_mangle]
+pub extern "C" fn interval_repetitions(tiv: &TempusInterval) -> i64 {
+    match tiv.repeat {
+        Repeat::Once => 1,
+        Repeat::Times(times) => times,
+        Repeat::Unbounded => -1,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn interval_series(tiv: &TempusInterval) -> Box<TempusIntervalSeries> {
+    Box::new(TempusIntervalSeries{interval: tiv.clone(), index: 0})
+}
+
+#[no_mangle]
+pub extern "C" fn interval_series_next(tivs: &mut TempusIntervalSeries, out_interval: *mut *mut TempusInterval) -> c_char {
+    match tivs.next() {
+        None => {
+            -1
+        }
+        Some(interval) => {
+            interval.stuff_into(out_interval);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn free_interval(tiv: Box<TempusInterval>) -> c_longlong {
+    let raw = Box::into_raw(tiv);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_interval_series(tivs: Box<TempusIntervalSeries>) -> c_longlong {
+    let raw = Box::into_raw(tivs);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
@@ -0,0 +1,326 @@
+use std::borrow::Cow;
+use std::ffi::c_longlong;
+use std::str::FromStr;
+use jiff::civil::{date, DateTime};
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Timestamp};
+use crate::datetime::TempusDateTime;
+use crate::duration::TempusSignedDuration;
+use crate::timestamp::{TempusTimestamp, NTP_UNIX_EPOCH};
+use crate::utils::{ahk_str_to_string, set_last_error_message, AHKWstr};
+
+const NANOSECONDS_PER_SECOND: i128 = 1_000_000_000;
+
+/// The largest TAI - UTC a table may hold, either way. It has only ever been 10 to 37 seconds.
+const MAX_TAI_OFFSET: i64 = 1000;
+
+/// The Unix time of 1980-01-06T00:00:00Z, the GPS epoch, when TAI - UTC was 19 seconds. GPS time has stayed 19
+/// seconds behind TAI since.
+const GPS_UNIX_EPOCH: i64 = 315_964_800;
+const GPS_TAI_OFFSET: i64 = 19;
+const SECONDS_PER_WEEK: i128 = 604_800;
+
+/// TT - TAI, in nanoseconds.
+const TT_TAI_OFFSET_NANOSECONDS: i128 = 32_184_000_000;
+
+/// The dates from which TAI - UTC changed, and its new value in seconds, from the start of leap seconds in 1972 up to
+/// the last one, at the end of 2016.
+const BUILTIN_LEAP_SECONDS: [(i16, i8, i64); 28] = [
+    (1972, 1, 10), (1972, 7, 11), (1973, 1, 12), (1974, 1, 13), (1975, 1, 14), (1976, 1, 15), (1977, 1, 16),
+    (1978, 1, 17), (1979, 1, 18), (1980, 1, 19), (1981, 7, 20), (1982, 7, 21), (1983, 7, 22), (1985, 7, 23),
+    (1988, 1, 24), (1990, 1, 25), (1991, 1, 26), (1992, 7, 27), (1993, 7, 28), (1994, 7, 29), (1996, 1, 30),
+    (1997, 7, 31), (1999, 1, 32), (2006, 1, 33), (2009, 1, 34), (2012, 7, 35), (2015, 7, 36), (2017, 1, 37),
+];
+
+/// A table of the offset between TAI and UTC over time. Each entry is the Unix time from which the offset applies and
+/// the offset in seconds, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeapSecondTable {
+    entries: Vec<(i64, i64)>,
+}
+
+impl LeapSecondTable {
+    pub fn builtin() -> Self {
+        let entries = BUILTIN_LEAP_SECONDS.iter().map(|&(year, month, offset)| {
+            (date(year, month, 1).to_zoned(TimeZone::UTC).unwrap().timestamp().as_second(), offset)
+        }).collect();
+        LeapSecondTable{entries}
+    }
+
+    /// TAI - UTC in seconds at the given UTC time in nanoseconds since 1970.
+    fn offset_at(&self, utc_nanoseconds: i128) -> Result<i64, String> {
+        let index = self.entries.partition_point(|&(start, _)| start as i128 * NANOSECONDS_PER_SECOND <= utc_nanoseconds);
+        if index == 0 {
+            return Err(format!("{} is before the start of the leap second table", self.first_change()))
+        }
+        Ok(self.entries[index - 1].1)
+    }
+
+    fn first_change(&self) -> Timestamp {
+        Timestamp::from_second(self.entries[0].0).unwrap()
+    }
+
+    /// Converts UTC nanoseconds since 1970 to TAI, counted as nanoseconds since 1970-01-01T00:00:00 TAI.
+    fn tai_from_utc(&self, utc_nanoseconds: i128) -> Result<i128, String> {
+        Ok(utc_nanoseconds + self.offset_at(utc_nanoseconds)? as i128 * NANOSECONDS_PER_SECOND)
+    }
+
+    /// Converts TAI back to UTC. UTC can't name an inserted leap second (23:59:60), so the TAI times during one come
+    /// out as the second after it.
+    fn utc_from_tai(&self, tai_nanoseconds: i128) -> Result<i128, String> {
+        let index = self.entries.partition_point(|&(start, offset)| (start + offset) as i128 * NANOSECONDS_PER_SECOND <= tai_nanoseconds);
+        if index == 0 {
+            return Err(format!("the TAI time is before the start of the leap second table ({})", self.first_change()))
+        }
+        Ok(tai_nanoseconds - self.entries[index - 1].1 as i128 * NANOSECONDS_PER_SECOND)
+    }
+}
+
+/// Parses the IETF/IERS `leap-seconds.list` format: lines of NTP seconds (since 1900-01-01) and the new TAI - UTC,
+/// with `#` starting a comment.
+impl FromStr for LeapSecondTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries: Vec<(i64, i64)> = Vec::new();
+        for (number, line) in s.lines().enumerate() {
+            let content = line.split('#').next().unwrap_or_default().trim();
+            if content.is_empty() {
+                continue
+            }
+            let mut fields = content.split_whitespace();
+            let (Some(seconds), Some(offset)) = (fields.next(), fields.next()) else {
+                return Err(format!("line {}: expected NTP seconds and TAI - UTC, got {line:?}", number + 1))
+            };
+            let (Ok(seconds), Ok(offset)) = (seconds.parse::<i64>(), offset.parse::<i64>()) else {
+                return Err(format!("line {}: expected NTP seconds and TAI - UTC, got {line:?}", number + 1))
+            };
+            let Some(start) = seconds.checked_sub(NTP_UNIX_EPOCH).filter(|&start| Timestamp::from_second(start).is_ok()) else {
+                return Err(format!("line {}: {seconds} is out of range", number + 1))
+            };
+            if !(-MAX_TAI_OFFSET..=MAX_TAI_OFFSET).contains(&offset) {
+                return Err(format!("line {}: a TAI - UTC of {offset} seconds is out of range", number + 1))
+            }
+            if entries.last().is_some_and(|&(previous, _)| previous >= start) {
+                return Err(format!("line {}: entries must be in order", number + 1))
+            }
+            entries.push((start, offset));
+        }
+        if entries.is_empty() {
+            return Err("the leap second table has no entries".to_string())
+        }
+        Ok(LeapSecondTable{entries})
+    }
+}
+
+#[repr(C)]
+pub struct TempusLeapSecondTable {
+    pub table: LeapSecondTable,
+}
+
+impl TempusLeapSecondTable {
+    pub fn stuff_into(self, pointer: *mut *mut TempusLeapSecondTable) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+/// The table to use when the caller passes none.
+fn table_or_builtin(table: Option<&TempusLeapSecondTable>) -> Cow<'_, LeapSecondTable> {
+    match table {
+        None => Cow::Owned(LeapSecondTable::builtin()),
+        Some(ttable) => Cow::Borrowed(&ttable.table),
+    }
+}
+
+fn timestamp_from_nanoseconds(nanoseconds: i128) -> Result<Timestamp, String> {
+    Timestamp::from_nanosecond(nanoseconds).map_err(|e| e.to_string())
+}
+
+/// Reads a civil datetime on the TAI or TT scale as nanoseconds since 1970-01-01T00:00:00 on that scale.
+fn scale_nanoseconds(datetime: DateTime) -> Result<i128, String> {
+    Ok(datetime.to_zoned(TimeZone::UTC).map_err(|e| e.to_string())?.timestamp().as_nanosecond())
+}
+
+fn scale_datetime(nanoseconds: i128) -> Result<DateTime, String> {
+    Ok(timestamp_from_nanoseconds(nanoseconds)?.to_zoned(TimeZone::UTC).datetime())
+}
+
+#[no_mangle]
+pub extern "C" fn leap_second_table_builtin() -> Box<TempusLeapSecondTable> {
+    Box::new(TempusLeapSecondTable{table: LeapSecondTable::builtin()})
+}
+
+#[no_mangle]
+pub extern "C" fn leap_second_table_parse(ahk_string: AHKWstr, out_table: *mut *mut TempusLeapSecondTable) -> c_longlong {
+    match ahk_str_to_string(ahk_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(s) => {
+            match LeapSecondTable::from_str(&s) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(table) => {
+                    TempusLeapSecondTable{table}.stuff_into(out_table);
+                    0
+                }
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn leap_second_table_len(ttable: &TempusLeapSecondTable) -> c_longlong {
+    ttable.table.entries.len() as c_longlong
+}
+
+/// TAI - UTC in seconds at the timestamp.
+#[no_mangle]
+pub extern "C" fn leap_second_table_tai_offset(ttable: &TempusLeapSecondTable, tts: &TempusTimestamp, out_offset: &mut i64) -> c_longlong {
+    match ttable.table.offset_at(tts.ts.as_nanosecond()) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(offset) => {
+            *out_offset = offset;
+            0
+        }
+    }
+}
+
+/// The timestamp as a civil datetime on the TAI scale. Without a table, the built-in one is used.
+#[no_mangle]
+pub extern "C" fn timestamp_to_tai(tts: &TempusTimestamp, ttable: Option<&TempusLeapSecondTable>, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    match table_or_builtin(ttable).tai_from_utc(tts.ts.as_nanosecond()).and_then(scale_datetime) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(datetime) => {
+            TempusDateTime{datetime}.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+/// The timestamp of a civil datetime on the TAI scale.
+#[no_mangle]
+pub extern "C" fn timestamp_from_tai(tdt: &TempusDateTime, ttable: Option<&TempusLeapSecondTable>, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    let table = table_or_builtin(ttable);
+    match scale_nanoseconds(tdt.datetime).and_then(|tai| table.utc_from_tai(tai)).and_then(timestamp_from_nanoseconds) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(ts) => {
+            TempusTimestamp{ts}.stuff_into(out_ts);
+            0
+        }
+    }
+}
+
+/// The timestamp as a civil datetime on the TT (Terrestrial Time) scale, which runs 32.184 seconds ahead of TAI.
+#[no_mangle]
+pub extern "C" fn timestamp_to_tt(tts: &TempusTimestamp, ttable: Option<&TempusLeapSecondTable>, out_datetime: *mut *mut TempusDateTime) -> c_longlong {
+    match table_or_builtin(ttable).tai_from_utc(tts.ts.as_nanosecond()).and_then(|tai| scale_datetime(tai + TT_TAI_OFFSET_NANOSECONDS)) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(datetime) => {
+            TempusDateTime{datetime}.stuff_into(out_datetime);
+            0
+        }
+    }
+}
+
+/// The timestamp of a civil datetime on the TT scale.
+#[no_mangle]
+pub extern "C" fn timestamp_from_tt(tdt: &TempusDateTime, ttable: Option<&TempusLeapSecondTable>, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    let table = table_or_builtin(ttable);
+    match scale_nanoseconds(tdt.datetime).and_then(|tt| table.utc_from_tai(tt - TT_TAI_OFFSET_NANOSECONDS)).and_then(timestamp_from_nanoseconds) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(ts) => {
+            TempusTimestamp{ts}.stuff_into(out_ts);
+            0
+        }
+    }
+}
+
+/// The timestamp as GPS time: the week number since 1980-01-06 (not rolled over at 1024) and the seconds into the week.
+#[no_mangle]
+pub extern "C" fn timestamp_to_gps(tts: &TempusTimestamp, ttable: Option<&TempusLeapSecondTable>, out_week: &mut i64, out_seconds: &mut f64) -> c_longlong {
+    let tai = match table_or_builtin(ttable).tai_from_utc(tts.ts.as_nanosecond()) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(tai) => tai
+    };
+    let gps = tai - (GPS_UNIX_EPOCH + GPS_TAI_OFFSET) as i128 * NANOSECONDS_PER_SECOND;
+    if gps < 0 {
+        set_last_error_message(format!("{} is before the GPS epoch (1980-01-06)", tts.ts));
+        return -2
+    }
+    *out_week = (gps / (SECONDS_PER_WEEK * NANOSECONDS_PER_SECOND)) as i64;
+    *out_seconds = (gps % (SECONDS_PER_WEEK * NANOSECONDS_PER_SECOND)) as f64 / NANOSECONDS_PER_SECOND as f64;
+    0
+}
+
+/// The timestamp of a GPS week number and seconds into the week, to the nearest nanosecond.
+#[no_mangle]
+pub extern "C" fn timestamp_from_gps(week: i64, seconds: f64, ttable: Option<&TempusLeapSecondTable>, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    if week < 0 || !(0.0..SECONDS_PER_WEEK as f64).contains(&seconds) {
+        set_last_error_message(format!("GPS week {week} and seconds {seconds} are out of range"));
+        return -1
+    }
+    let gps = week as i128 * SECONDS_PER_WEEK * NANOSECONDS_PER_SECOND + (seconds * NANOSECONDS_PER_SECOND as f64).round() as i128;
+    let tai = gps + (GPS_UNIX_EPOCH + GPS_TAI_OFFSET) as i128 * NANOSECONDS_PER_SECOND;
+    match table_or_builtin(ttable).utc_from_tai(tai).and_then(timestamp_from_nanoseconds) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(ts) => {
+            TempusTimestamp{ts}.stuff_into(out_ts);
+            0
+        }
+    }
+}
+
+/// The time that actually elapses from the timestamp to `other`, counting the leap seconds in between (which
+/// `timestamp_duration_until` doesn't).
+#[no_mangle]
+pub extern "C" fn timestamp_elapsed_until(tts: &TempusTimestamp, other: &TempusTimestamp, ttable: Option<&TempusLeapSecondTable>, out_duration: *mut *mut TempusSignedDuration) -> c_longlong {
+    let table = table_or_builtin(ttable);
+    let elapsed = match (table.tai_from_utc(tts.ts.as_nanosecond()), table.tai_from_utc(other.ts.as_nanosecond())) {
+        (Ok(start), Ok(end)) => end - start,
+        (Err(e), _) | (_, Err(e)) => {
+            set_last_error_message(e);
+            return -1
+        }
+    };
+    let seconds = (elapsed / NANOSECONDS_PER_SECOND) as i64;
+    let nanoseconds = (elapsed % NANOSECONDS_PER_SECOND) as i32;
+    TempusSignedDuration{duration: SignedDuration::new(seconds, nanoseconds)}.stuff_into(out_duration);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_leap_second_table(ttable: Box<TempusLeapSecondTable>) -> c_longlong {
+    let raw = Box::into_raw(ttable);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
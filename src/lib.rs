@@ -10,6 +10,22 @@ pub mod date;
 pub mod time;
 pub mod datetime;
 pub mod isoweekdate;
+pub mod yearmonth;
+pub mod monthday;
+pub mod range;
+pub mod daterange;
+pub mod timestamprange;
+pub mod interval;
+pub mod freebusy;
+pub mod business;
+pub mod holiday;
+pub mod workhours;
+pub mod fiscal;
+pub mod rrule;
+pub mod cron;
+pub mod ical;
+pub mod leapseconds;
+pub mod calendars;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,160 @@
+use std::cmp::Ordering;
+use std::ffi::{c_char, c_longlong};
+use std::str::FromStr;
+use jiff::civil::Date;
+use crate::date::{date_from_fields, TempusDate};
+use crate::utils::{ahk_str_to_string, overflow_from_i8, set_last_error_message, string_into_ahk_buff, AHKStringBuffer, AHKWstr};
+
+/// A day of the year without a year, like Temporal's `PlainMonthDay`. February 29 is allowed.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TempusMonthDay {
+    pub month: i8,
+    pub day: i8,
+}
+
+impl TempusMonthDay {
+    pub fn new(month: i8, day: i8) -> Result<Self, String> {
+        // 2000 is a leap year, so this accepts exactly the month/day pairs that exist in some year
+        Date::new(2000, month, day).map_err(|e| e.to_string())?;
+        Ok(TempusMonthDay{month, day})
+    }
+
+    pub fn stuff_into(self, pointer: *mut *mut TempusMonthDay) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+impl FromStr for TempusMonthDay {
+    type Err = String;
+
+    /// Accepts `--MM-DD` (RFC 3339), `MM-DD` and `--MMDD`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("failed to parse '{s}' as a month-day (expected a string like --12-25)");
+        let rest = s.strip_prefix("--").unwrap_or(s);
+        let (month_str, day_str) = match rest.split_once('-') {
+            Some(parts) => parts,
+            None if rest.len() == 4 && rest.is_char_boundary(2) && s.starts_with("--") => rest.split_at(2),
+            None => return Err(err()),
+        };
+        if month_str.len() != 2 || day_str.len() != 2 || !month_str.bytes().chain(day_str.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(err())
+        }
+        let month: i8 = month_str.parse().map_err(|_| err())?;
+        let day: i8 = day_str.parse().map_err(|_| err())?;
+        TempusMonthDay::new(month, day)
+    }
+}
+
+impl std::fmt::Display for TempusMonthDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "--{:02}-{:02}", self.month, self.day)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn monthday_new(month: i8, day: i8, out_monthday: *mut *mut TempusMonthDay) -> c_longlong {
+    match TempusMonthDay::new(month, day) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(tmd) => {
+            tmd.stuff_into(out_monthday);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn monthday_parse(ahk_string: AHKWstr, out_monthday: *mut *mut TempusMonthDay) -> c_longlong {
+    match ahk_str_to_string(ahk_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(s) => {
+            match TempusMonthDay::from_str(&s) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(tmd) => {
+                    tmd.stuff_into(out_monthday);
+                    0
+                }
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn monthday_from_date(tdate: &TempusDate) -> Box<TempusMonthDay> {
+    Box::new(TempusMonthDay{month: tdate.date.month(), day: tdate.date.day()})
+}
+
+#[no_mangle]
+pub extern "C" fn monthday_string_length(tmd: &TempusMonthDay) -> usize {
+    tmd.to_string().len()
+}
+
+#[no_mangle]
+pub extern "C" fn monthday_to_string(tmd: &TempusMonthDay, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(tmd.to_string(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn monthday_compare(tmd: &TempusMonthDay, other: &TempusMonthDay) -> c_char {
+    match tmd.cmp(other) {
+        Ordering::Less => {-1}
+        Ordering::Equal => {0}
+        Ordering::Greater => {1}
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn monthday_month(tmd: &TempusMonthDay) -> c_char {
+    tmd.month
+}
+
+#[no_mangle]
+pub extern "C" fn monthday_day(tmd: &TempusMonthDay) -> c_char {
+    tmd.day
+}
+
+/// Returns this month-day in the given year. February 29 in a common year is resolved by the overflow mode:
+/// Reject returns an error, Constrain gives February 28 and Balance gives March 1.
+#[no_mangle]
+pub extern "C" fn monthday_to_date(tmd: &TempusMonthDay, year: i16, overflow_i: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    let overflow = match overflow_from_i8(overflow_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(overflow) => overflow
+    };
+    match date_from_fields(year as i64, tmd.month as i64, tmd.day as i64, overflow) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok(date) => {
+            let td = TempusDate{date};
+            td.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn free_monthday(tmd: Box<TempusMonthDay>) -> c_longlong {
+    let raw = Box::into_raw(tmd);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
@@ -0,0 +1,156 @@
+use jiff::Span;
+
+/// The half-open interval `[start, end)` shared by the range types. Closed ranges are converted to this form when
+/// they're created, so the set operations only have to deal with one kind of interval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HalfOpen<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Ord + Copy> HalfOpen<T> {
+    pub fn new(start: T, end: T) -> Result<Self, String> {
+        if end < start {
+            return Err("the end of a range must not be before its start".to_string())
+        }
+        Ok(HalfOpen{start, end})
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn contains_range(&self, other: &HalfOpen<T>) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &HalfOpen<T>) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    pub fn intersection(&self, other: &HalfOpen<T>) -> Option<HalfOpen<T>> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        if start < end {
+            Some(HalfOpen{start, end})
+        } else {
+            None
+        }
+    }
+
+    /// The union of two ranges, if they overlap or are adjacent (so that the union is itself a range).
+    pub fn union(&self, other: &HalfOpen<T>) -> Option<HalfOpen<T>> {
+        if self.start <= other.end && other.start <= self.end {
+            Some(HalfOpen{start: self.start.min(other.start), end: self.end.max(other.end)})
+        } else {
+            None
+        }
+    }
+
+    /// The range between two ranges that neither overlap nor are adjacent.
+    pub fn gap(&self, other: &HalfOpen<T>) -> Option<HalfOpen<T>> {
+        if self.end < other.start {
+            Some(HalfOpen{start: self.end, end: other.start})
+        } else if other.end < self.start {
+            Some(HalfOpen{start: other.end, end: self.start})
+        } else {
+            None
+        }
+    }
+}
+
+/// Sorts a set of ranges and merges the ones that overlap or touch. Empty ranges are dropped.
+pub fn normalize<T: Ord + Copy>(mut ranges: Vec<HalfOpen<T>>) -> Vec<HalfOpen<T>> {
+    ranges.retain(|range| !range.is_empty());
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<HalfOpen<T>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => {
+                last.end = last.end.max(range.end);
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// The parts of two normalized sets of ranges that are in both.
+pub fn intersect_sets<T: Ord + Copy>(a: &[HalfOpen<T>], b: &[HalfOpen<T>]) -> Vec<HalfOpen<T>> {
+    let mut ret = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if let Some(range) = a[i].intersection(&b[j]) {
+            ret.push(range);
+        }
+        if a[i].end < b[j].end {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    ret
+}
+
+/// The parts of a normalized set of ranges that are not covered by any range in the normalized set `remove`.
+pub fn subtract_sets<T: Ord + Copy>(a: &[HalfOpen<T>], remove: &[HalfOpen<T>]) -> Vec<HalfOpen<T>> {
+    let mut ret = Vec::new();
+    for range in a {
+        let mut start = range.start;
+        for removed in remove.iter().filter(|removed| removed.overlaps(range)) {
+            if start < removed.start {
+                ret.push(HalfOpen{start, end: removed.start});
+            }
+            start = start.max(removed.end);
+        }
+        if start < range.end {
+            ret.push(HalfOpen{start, end: range.end});
+        }
+    }
+    ret
+}
+
+/// Splits a range into consecutive pieces of `step` each. The last piece is cut short at the end of the range.
+/// Piece boundaries are always measured from the start of the range, so that splitting by months doesn't drift
+/// after a short month.
+pub struct RangeSplit<T> {
+    range: HalfOpen<T>,
+    step: Span,
+    add: fn(T, Span) -> Result<T, jiff::Error>,
+    next_start: T,
+    pieces: i64,
+}
+
+impl<T: Ord + Copy> RangeSplit<T> {
+    pub fn new(range: HalfOpen<T>, step: Span, add: fn(T, Span) -> Result<T, jiff::Error>) -> Result<Self, String> {
+        if !range.is_empty() {
+            let first_end = add(range.start, step).map_err(|e| e.to_string())?;
+            if first_end <= range.start {
+                return Err("the span to split a range by must be positive".to_string())
+            }
+        }
+        Ok(RangeSplit{range, step, add, next_start: range.start, pieces: 0})
+    }
+}
+
+impl<T: Ord + Copy> Iterator for RangeSplit<T> {
+    type Item = HalfOpen<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.next_start;
+        if start >= self.range.end {
+            return None
+        }
+        self.pieces += 1;
+        let end = match self.step.checked_mul(self.pieces).and_then(|step| (self.add)(self.range.start, step)) {
+            Ok(end) if end < self.range.end => end,
+            _ => self.range.end,
+        };
+        self.next_start = end;
+        Some(HalfOpen{start, end})
+    }
+}
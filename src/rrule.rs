@@ -0,0 +1,689 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::ffi::{c_char, c_longlong};
+use std::str::FromStr;
+use jiff::civil::{Date, DateTime, Time, Weekday};
+use jiff::{Span, Timestamp, Zoned};
+use crate::date::week_of_year_of_date;
+use crate::utils::{ahk_str_to_string, set_last_error_message, string_into_ahk_buff, AHKStringBuffer, AHKWstr};
+use crate::zoned::TempusZoned;
+
+/// Ordered from the shortest period to the longest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Frequency {
+    Secondly,
+    Minutely,
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    fn name(self) -> &'static str {
+        match self {
+            Frequency::Secondly => "SECONDLY",
+            Frequency::Minutely => "MINUTELY",
+            Frequency::Hourly => "HOURLY",
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Until {
+    Date(Date),
+    /// A local ("floating") datetime, in the time zone of DTSTART.
+    DateTime(DateTime),
+    Timestamp(Timestamp),
+}
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Monday), ("TU", Weekday::Tuesday), ("WE", Weekday::Wednesday), ("TH", Weekday::Thursday),
+    ("FR", Weekday::Friday), ("SA", Weekday::Saturday), ("SU", Weekday::Sunday),
+];
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    WEEKDAYS.iter().find(|(_, wd)| *wd == weekday).map(|(code, _)| *code).unwrap_or("MO")
+}
+
+fn parse_weekday(s: &str) -> Result<Weekday, String> {
+    WEEKDAYS.iter().find(|(code, _)| *code == s).map(|(_, weekday)| *weekday).ok_or_else(|| format!("invalid weekday '{s}'"))
+}
+
+fn parse_list<T: FromStr + PartialOrd + Copy + std::ops::Neg<Output = T>>(key: &str, value: &str, min: T, max: T, allow_negative: bool) -> Result<Vec<T>, String> {
+    let mut ret = Vec::new();
+    for item in value.split(',') {
+        let n: T = item.trim().parse().map_err(|_| format!("invalid {key} value '{item}'"))?;
+        let in_range = (min <= n && n <= max) || (allow_negative && -max <= n && n <= -min);
+        if !in_range {
+            return Err(format!("{key} value {item} is out of range"))
+        }
+        ret.push(n);
+    }
+    Ok(ret)
+}
+
+fn parse_until(s: &str) -> Result<Until, String> {
+    let err = || format!("invalid UNTIL value '{s}' (expected a date like 20251231 or a datetime like 20251231T235959Z)");
+    let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    let (date_str, time_str) = match s.split_once('T') {
+        Some((date_str, time_str)) => (date_str, Some(time_str)),
+        None => (s, None),
+    };
+    if date_str.len() != 8 || !digits(date_str) {
+        return Err(err())
+    }
+    let date = Date::new(
+        date_str[..4].parse().map_err(|_| err())?,
+        date_str[4..6].parse().map_err(|_| err())?,
+        date_str[6..].parse().map_err(|_| err())?,
+    ).map_err(|e| e.to_string())?;
+    let Some(time_str) = time_str else {
+        return Ok(Until::Date(date))
+    };
+    let (time_str, utc) = match time_str.strip_suffix('Z') {
+        Some(time_str) => (time_str, true),
+        None => (time_str, false),
+    };
+    if time_str.len() != 6 || !digits(time_str) {
+        return Err(err())
+    }
+    let time = Time::new(
+        time_str[..2].parse().map_err(|_| err())?,
+        time_str[2..4].parse().map_err(|_| err())?,
+        time_str[4..].parse().map_err(|_| err())?,
+        0,
+    ).map_err(|e| e.to_string())?;
+    let datetime = date.to_datetime(time);
+    if utc {
+        let timestamp = datetime.to_zoned(jiff::tz::TimeZone::UTC).map_err(|e| e.to_string())?.timestamp();
+        Ok(Until::Timestamp(timestamp))
+    } else {
+        Ok(Until::DateTime(datetime))
+    }
+}
+
+/// An RFC 5545 recurrence rule (the value of an `RRULE` property).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RRule {
+    freq: Frequency,
+    interval: i64,
+    count: Option<u64>,
+    until: Option<Until>,
+    bysecond: Vec<i8>,
+    byminute: Vec<i8>,
+    byhour: Vec<i8>,
+    /// A weekday, optionally with the number of the occurrence within the month or year (0 means every one).
+    byday: Vec<(i8, Weekday)>,
+    bymonthday: Vec<i8>,
+    byyearday: Vec<i16>,
+    byweekno: Vec<i8>,
+    bymonth: Vec<i8>,
+    bysetpos: Vec<i16>,
+    wkst: Weekday,
+}
+
+impl FromStr for RRule {
+    type Err = String;
+
+    /// Parses a rule like `FREQ=MONTHLY;BYDAY=2TU,4TU`, with or without the `RRULE:` property name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = if s.get(..6).is_some_and(|prefix| prefix.eq_ignore_ascii_case("RRULE:")) { &s[6..] } else { s };
+        let mut freq = None;
+        let mut rule = RRule{
+            freq: Frequency::Daily, interval: 1, count: None, until: None, bysecond: Vec::new(), byminute: Vec::new(),
+            byhour: Vec::new(), byday: Vec::new(), bymonthday: Vec::new(), byyearday: Vec::new(), byweekno: Vec::new(),
+            bymonth: Vec::new(), bysetpos: Vec::new(), wkst: Weekday::Monday,
+        };
+        let mut seen = BTreeSet::new();
+        for part in s.split(';').filter(|part| !part.trim().is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(|| format!("expected KEY=VALUE, got '{part}'"))?;
+            let key = key.trim().to_ascii_uppercase();
+            let value = value.trim().to_ascii_uppercase();
+            if !seen.insert(key.clone()) {
+                return Err(format!("{key} is given more than once"))
+            }
+            match key.as_str() {
+                "FREQ" => {
+                    freq = Some(match value.as_str() {
+                        "SECONDLY" => Frequency::Secondly,
+                        "MINUTELY" => Frequency::Minutely,
+                        "HOURLY" => Frequency::Hourly,
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("invalid FREQ '{value}'"))
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value.parse().map_err(|_| format!("invalid INTERVAL '{value}'"))?;
+                    if rule.interval < 1 {
+                        return Err("INTERVAL must be a positive integer".to_string())
+                    }
+                }
+                "COUNT" => rule.count = Some(value.parse().map_err(|_| format!("invalid COUNT '{value}'"))?),
+                "UNTIL" => rule.until = Some(parse_until(&value)?),
+                "BYSECOND" => rule.bysecond = parse_list(&key, &value, 0, 59, false)?,
+                "BYMINUTE" => rule.byminute = parse_list(&key, &value, 0, 59, false)?,
+                "BYHOUR" => rule.byhour = parse_list(&key, &value, 0, 23, false)?,
+                "BYMONTHDAY" => rule.bymonthday = parse_list(&key, &value, 1, 31, true)?,
+                "BYYEARDAY" => rule.byyearday = parse_list(&key, &value, 1, 366, true)?,
+                "BYWEEKNO" => rule.byweekno = parse_list(&key, &value, 1, 53, true)?,
+                "BYMONTH" => rule.bymonth = parse_list(&key, &value, 1, 12, false)?,
+                "BYSETPOS" => rule.bysetpos = parse_list(&key, &value, 1, 366, true)?,
+                "WKST" => rule.wkst = parse_weekday(&value)?,
+                "BYDAY" => {
+                    for item in value.split(',') {
+                        let item = item.trim();
+                        if item.len() < 2 || !item.is_char_boundary(item.len() - 2) {
+                            return Err(format!("invalid BYDAY value '{item}'"))
+                        }
+                        let (nth_str, code) = item.split_at(item.len() - 2);
+                        let weekday = parse_weekday(code)?;
+                        let nth: i8 = if nth_str.is_empty() {
+                            0
+                        } else {
+                            nth_str.strip_prefix('+').unwrap_or(nth_str).parse().map_err(|_| format!("invalid BYDAY value '{item}'"))?
+                        };
+                        if !nth_str.is_empty() && (nth == 0 || !(-53..=53).contains(&nth)) {
+                            return Err(format!("BYDAY value {item} is out of range"))
+                        }
+                        rule.byday.push((nth, weekday));
+                    }
+                }
+                _ => return Err(format!("unsupported rule part '{key}'"))
+            }
+        }
+        rule.freq = freq.ok_or("FREQ is required")?;
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err("COUNT and UNTIL cannot both be given".to_string())
+        }
+        if !rule.byweekno.is_empty() && rule.freq != Frequency::Yearly {
+            return Err("BYWEEKNO is only valid with FREQ=YEARLY".to_string())
+        }
+        if !rule.byyearday.is_empty() && matches!(rule.freq, Frequency::Daily | Frequency::Weekly | Frequency::Monthly) {
+            return Err(format!("BYYEARDAY is not valid with FREQ={}", rule.freq.name()))
+        }
+        if !rule.bymonthday.is_empty() && rule.freq == Frequency::Weekly {
+            return Err("BYMONTHDAY is not valid with FREQ=WEEKLY".to_string())
+        }
+        if rule.byday.iter().any(|(nth, _)| *nth != 0) {
+            if !matches!(rule.freq, Frequency::Monthly | Frequency::Yearly) {
+                return Err("a numbered BYDAY is only valid with FREQ=MONTHLY or FREQ=YEARLY".to_string())
+            }
+            if !rule.byweekno.is_empty() {
+                return Err("a numbered BYDAY is not valid together with BYWEEKNO".to_string())
+            }
+        }
+        if !rule.bysetpos.is_empty() && rule.bysecond.is_empty() && rule.byminute.is_empty() && rule.byhour.is_empty()
+            && rule.byday.is_empty() && rule.bymonthday.is_empty() && rule.byyearday.is_empty()
+            && rule.byweekno.is_empty() && rule.bymonth.is_empty() {
+            return Err("BYSETPOS must be used together with another BYxxx rule part".to_string())
+        }
+        Ok(rule)
+    }
+}
+
+impl std::fmt::Display for RRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn list<T: std::fmt::Display>(items: &[T]) -> String {
+            items.iter().map(|item| item.to_string()).collect::<Vec<_>>().join(",")
+        }
+        write!(f, "FREQ={}", self.freq.name())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        match self.until {
+            None => {}
+            Some(Until::Date(date)) => write!(f, ";UNTIL={}", date.strftime("%Y%m%d"))?,
+            Some(Until::DateTime(datetime)) => write!(f, ";UNTIL={}", datetime.strftime("%Y%m%dT%H%M%S"))?,
+            Some(Until::Timestamp(timestamp)) => write!(f, ";UNTIL={}", timestamp.strftime("%Y%m%dT%H%M%SZ"))?,
+        }
+        let parts: [(&str, String); 8] = [
+            ("BYSECOND", list(&self.bysecond)),
+            ("BYMINUTE", list(&self.byminute)),
+            ("BYHOUR", list(&self.byhour)),
+            ("BYDAY", self.byday.iter().map(|(nth, weekday)| {
+                if *nth == 0 { weekday_code(*weekday).to_string() } else { format!("{nth}{}", weekday_code(*weekday)) }
+            }).collect::<Vec<_>>().join(",")),
+            ("BYMONTHDAY", list(&self.bymonthday)),
+            ("BYYEARDAY", list(&self.byyearday)),
+            ("BYWEEKNO", list(&self.byweekno)),
+            ("BYMONTH", list(&self.bymonth)),
+        ];
+        for (key, value) in parts {
+            if !value.is_empty() {
+                write!(f, ";{key}={value}")?;
+            }
+        }
+        if !self.bysetpos.is_empty() {
+            write!(f, ";BYSETPOS={}", list(&self.bysetpos))?;
+        }
+        if self.wkst != Weekday::Monday {
+            write!(f, ";WKST={}", weekday_code(self.wkst))?;
+        }
+        Ok(())
+    }
+}
+
+/// Expands a recurrence rule from DTSTART. Each period of the rule's frequency is expanded into candidate dates and
+/// times, which are then limited by the BYxxx rule parts (as described in RFC 5545 section 3.3.10).
+pub struct RRuleIter {
+    /// The rule, with the parts that default to DTSTART's fields filled in.
+    rule: RRule,
+    start: Zoned,
+    period: i64,
+    pending: VecDeque<Zoned>,
+    emitted: u64,
+    last: Option<Timestamp>,
+    done: bool,
+}
+
+/// Gives up on a rule after this many periods in a row without an occurrence (like February 30).
+const MAX_EMPTY_PERIODS: u32 = 100_000;
+
+impl RRuleIter {
+    pub fn new(rule: &RRule, start: Zoned) -> Self {
+        let mut rule = rule.clone();
+        let local = start.datetime();
+        if rule.byweekno.is_empty() && rule.byyearday.is_empty() && rule.bymonthday.is_empty() && rule.byday.is_empty() {
+            match rule.freq {
+                Frequency::Yearly => {
+                    if rule.bymonth.is_empty() {
+                        rule.bymonth = vec![local.month()];
+                    }
+                    rule.bymonthday = vec![local.day()];
+                }
+                Frequency::Monthly => rule.bymonthday = vec![local.day()],
+                Frequency::Weekly => rule.byday = vec![(0, local.weekday())],
+                _ => {}
+            }
+        }
+        if rule.freq > Frequency::Hourly && rule.byhour.is_empty() {
+            rule.byhour = vec![local.hour()];
+        }
+        if rule.freq > Frequency::Minutely && rule.byminute.is_empty() {
+            rule.byminute = vec![local.minute()];
+        }
+        if rule.freq > Frequency::Secondly && rule.bysecond.is_empty() {
+            rule.bysecond = vec![local.second()];
+        }
+        RRuleIter{rule, start, period: 0, pending: VecDeque::new(), emitted: 0, last: None, done: false}
+    }
+
+    /// The first moment of the given period, as a local datetime.
+    fn period_start(&self, period: i64) -> Result<DateTime, jiff::Error> {
+        let local = self.start.datetime();
+        let n = period * self.rule.interval;
+        match self.rule.freq {
+            Frequency::Yearly => Ok(local.date().first_of_year().checked_add(Span::new().years(n))?.to_datetime(Time::midnight())),
+            Frequency::Monthly => Ok(local.date().first_of_month().checked_add(Span::new().months(n))?.to_datetime(Time::midnight())),
+            Frequency::Weekly => {
+                let week_start = local.date().checked_sub(Span::new().days(local.weekday().since(self.rule.wkst)))?;
+                Ok(week_start.checked_add(Span::new().weeks(n))?.to_datetime(Time::midnight()))
+            }
+            Frequency::Daily => Ok(local.date().checked_add(Span::new().days(n))?.to_datetime(Time::midnight())),
+            Frequency::Hourly => local.with().minute(0).second(0).subsec_nanosecond(0).build()?.checked_add(Span::new().hours(n)),
+            Frequency::Minutely => local.with().second(0).subsec_nanosecond(0).build()?.checked_add(Span::new().minutes(n)),
+            Frequency::Secondly => local.with().subsec_nanosecond(0).build()?.checked_add(Span::new().seconds(n)),
+        }
+    }
+
+    fn day_matches(&self, date: Date) -> bool {
+        let rule = &self.rule;
+        if !rule.bymonth.is_empty() && !rule.bymonth.contains(&date.month()) {
+            return false
+        }
+        if !rule.byweekno.is_empty() {
+            let Ok((week_year, week)) = week_of_year_of_date(date, rule.wkst, 4) else { return false };
+            // December 28 is always in the last week of its year when week 1 needs at least four days
+            let weeks = Date::new(week_year, 12, 28).ok()
+                .and_then(|last| week_of_year_of_date(last, rule.wkst, 4).ok())
+                .map(|(_, weeks)| weeks)
+                .unwrap_or(52);
+            if !rule.byweekno.iter().any(|n| *n == week || *n == week - weeks - 1) {
+                return false
+            }
+        }
+        if !rule.byyearday.is_empty() {
+            let day = date.day_of_year();
+            let days = date.days_in_year();
+            if !rule.byyearday.iter().any(|n| *n == day || *n == day - days - 1) {
+                return false
+            }
+        }
+        if !rule.bymonthday.is_empty() {
+            let day = date.day();
+            let days = date.days_in_month();
+            if !rule.bymonthday.iter().any(|n| *n == day || *n == day - days - 1) {
+                return false
+            }
+        }
+        if !rule.byday.is_empty() {
+            // numbered weekdays count within the month, unless the rule is yearly without BYMONTH
+            let in_month = rule.freq == Frequency::Monthly || !rule.bymonth.is_empty();
+            let (day, days) = if in_month {
+                (date.day() as i16, date.days_in_month() as i16)
+            } else {
+                (date.day_of_year(), date.days_in_year())
+            };
+            let from_start = ((day - 1) / 7 + 1) as i8;
+            let from_end = -(((days - day) / 7 + 1) as i8);
+            let matches = rule.byday.iter().any(|(nth, weekday)| {
+                *weekday == date.weekday() && (*nth == 0 || *nth == from_start || *nth == from_end)
+            });
+            if !matches {
+                return false
+            }
+        }
+        true
+    }
+
+    /// The times of day within a period that starts at `period_start`.
+    fn times(&self, period_start: DateTime) -> Vec<Time> {
+        let rule = &self.rule;
+        let allowed = |values: &[i8], value: i8| values.is_empty() || values.contains(&value);
+        let (hours, minutes, seconds) = match rule.freq {
+            Frequency::Hourly => {
+                if !allowed(&rule.byhour, period_start.hour()) {
+                    return Vec::new()
+                }
+                (vec![period_start.hour()], rule.byminute.clone(), rule.bysecond.clone())
+            }
+            Frequency::Minutely => {
+                if !allowed(&rule.byhour, period_start.hour()) || !allowed(&rule.byminute, period_start.minute()) {
+                    return Vec::new()
+                }
+                (vec![period_start.hour()], vec![period_start.minute()], rule.bysecond.clone())
+            }
+            Frequency::Secondly => {
+                if !allowed(&rule.byhour, period_start.hour()) || !allowed(&rule.byminute, period_start.minute())
+                    || !allowed(&rule.bysecond, period_start.second()) {
+                    return Vec::new()
+                }
+                (vec![period_start.hour()], vec![period_start.minute()], vec![period_start.second()])
+            }
+            _ => (rule.byhour.clone(), rule.byminute.clone(), rule.bysecond.clone()),
+        };
+        let mut times = Vec::new();
+        for hour in &hours {
+            for minute in &minutes {
+                for second in &seconds {
+                    if let Ok(time) = Time::new(*hour, *minute, *second, 0) {
+                        times.push(time);
+                    }
+                }
+            }
+        }
+        times.sort();
+        times.dedup();
+        times
+    }
+
+    /// The candidate days of a period.
+    fn days(&self, period_start: DateTime) -> Vec<Date> {
+        let first = period_start.date();
+        let (first, count) = match self.rule.freq {
+            Frequency::Yearly => (first, first.days_in_year() as i64),
+            Frequency::Monthly => (first, first.days_in_month() as i64),
+            Frequency::Weekly => (first, 7),
+            _ => (first, 1),
+        };
+        let mut days = Vec::new();
+        let mut date = first;
+        for _ in 0..count {
+            if self.day_matches(date) {
+                days.push(date);
+            }
+            date = match date.tomorrow() {
+                Ok(next) => next,
+                Err(_) => break,
+            };
+        }
+        days
+    }
+
+    /// Expands the next period with occurrences into `pending`.
+    fn fill(&mut self) {
+        let mut empty_periods = 0;
+        while self.pending.is_empty() {
+            if empty_periods > MAX_EMPTY_PERIODS {
+                self.done = true;
+                return
+            }
+            let Ok(period_start) = self.period_start(self.period) else {
+                self.done = true;
+                return
+            };
+            if self.rule.freq < Frequency::Daily && !self.day_matches(period_start.date()) {
+                // skip the rest of the day, staying on the rule's interval
+                let elapsed = match self.rule.freq {
+                    Frequency::Hourly => period_start.hour() as i64,
+                    Frequency::Minutely => period_start.hour() as i64 * 60 + period_start.minute() as i64,
+                    _ => period_start.hour() as i64 * 3600 + period_start.minute() as i64 * 60 + period_start.second() as i64,
+                };
+                let per_day = match self.rule.freq {
+                    Frequency::Hourly => 24,
+                    Frequency::Minutely => 24 * 60,
+                    _ => 24 * 3600,
+                };
+                let remaining = per_day - elapsed;
+                self.period += (remaining + self.rule.interval - 1) / self.rule.interval;
+                empty_periods += 1;
+                continue
+            }
+            let times = self.times(period_start);
+            let mut occurrences: Vec<DateTime> = Vec::new();
+            if !times.is_empty() {
+                for date in self.days(period_start) {
+                    for time in &times {
+                        occurrences.push(date.to_datetime(*time));
+                    }
+                }
+            }
+            if !self.rule.bysetpos.is_empty() {
+                let len = occurrences.len() as i64;
+                let mut selected: Vec<DateTime> = self.rule.bysetpos.iter().filter_map(|pos| {
+                    let index = if *pos > 0 { *pos as i64 - 1 } else { len + *pos as i64 };
+                    usize::try_from(index).ok().and_then(|index| occurrences.get(index)).copied()
+                }).collect();
+                selected.sort();
+                selected.dedup();
+                occurrences = selected;
+            }
+            for datetime in occurrences {
+                let Ok(zoned) = datetime.to_zoned(self.start.time_zone().clone()) else { continue };
+                let timestamp = zoned.timestamp();
+                if timestamp < self.start.timestamp() || self.last.is_some_and(|last| timestamp <= last) {
+                    continue
+                }
+                self.last = Some(timestamp);
+                self.pending.push_back(zoned);
+            }
+            self.period += 1;
+            empty_periods += 1;
+        }
+    }
+
+    fn is_past_until(&self, zoned: &Zoned) -> bool {
+        match self.rule.until {
+            None => false,
+            Some(Until::Date(date)) => zoned.date() > date,
+            Some(Until::DateTime(datetime)) => zoned.datetime() > datetime,
+            Some(Until::Timestamp(timestamp)) => zoned.timestamp() > timestamp,
+        }
+    }
+}
+
+impl Iterator for RRuleIter {
+    type Item = Zoned;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(zoned) = self.pending.pop_front() {
+                if self.rule.count.is_some_and(|count| self.emitted >= count) || self.is_past_until(&zoned) {
+                    self.done = true;
+                    self.pending.clear();
+                    return None
+                }
+                self.emitted += 1;
+                return Some(zoned)
+            }
+            if self.done {
+                return None
+            }
+            self.fill();
+        }
+    }
+}
+
+/// A recurrence: a rule and its DTSTART, plus extra dates (RDATE) and excluded dates (EXDATE).
+#[repr(C)]
+pub struct TempusRRule {
+    rule: RRule,
+    dtstart: Zoned,
+    rdates: Vec<Zoned>,
+    exdates: Vec<Timestamp>,
+}
+
+impl TempusRRule {
+    pub fn stuff_into(self, pointer: *mut *mut TempusRRule) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+/// The occurrences of a recurrence in order, with the RDATEs merged in and the EXDATEs left out.
+#[repr(C)]
+pub struct TempusRRuleSeries {
+    occurrences: std::iter::Peekable<RRuleIter>,
+    rdates: VecDeque<Zoned>,
+    exdates: BTreeSet<Timestamp>,
+    last: Option<Timestamp>,
+}
+
+impl Iterator for TempusRRuleSeries {
+    type Item = Zoned;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let from_rule = match (self.occurrences.peek(), self.rdates.front()) {
+                (None, None) => return None,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (Some(occurrence), Some(rdate)) => occurrence.timestamp() <= rdate.timestamp(),
+            };
+            let zoned = if from_rule { self.occurrences.next()? } else { self.rdates.pop_front()? };
+            let timestamp = zoned.timestamp();
+            if self.exdates.contains(&timestamp) || self.last == Some(timestamp) {
+                continue
+            }
+            self.last = Some(timestamp);
+            return Some(zoned)
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rrule_parse(ahk_string: AHKWstr, dtstart: &TempusZoned, out_rrule: *mut *mut TempusRRule) -> c_longlong {
+    match ahk_str_to_string(ahk_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(s) => {
+            match RRule::from_str(&s) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(rule) => {
+                    let trr = TempusRRule{rule, dtstart: dtstart.zoned.clone(), rdates: Vec::new(), exdates: Vec::new()};
+                    trr.stuff_into(out_rrule);
+                    0
+                }
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rrule_add_rdate(trr: &mut TempusRRule, tzoned: &TempusZoned) -> c_longlong {
+    trr.rdates.push(tzoned.zoned.clone());
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn rrule_add_exdate(trr: &mut TempusRRule, tzoned: &TempusZoned) -> c_longlong {
+    trr.exdates.push(tzoned.zoned.timestamp());
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn rrule_dtstart(trr: &TempusRRule) -> Box<TempusZoned> {
+    Box::new(TempusZoned{zoned: trr.dtstart.clone()})
+}
+
+#[no_mangle]
+pub extern "C" fn rrule_string_length(trr: &TempusRRule) -> usize {
+    trr.rule.to_string().len()
+}
+
+#[no_mangle]
+pub extern "C" fn rrule_to_string(trr: &TempusRRule, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(trr.rule.to_string(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn rrule_series(trr: &TempusRRule) -> Box<TempusRRuleSeries> {
+    let mut rdates = trr.rdates.clone();
+    rdates.sort_by_key(|zoned| zoned.timestamp());
+    Box::new(TempusRRuleSeries{
+        occurrences: RRuleIter::new(&trr.rule, trr.dtstart.clone()).peekable(),
+        rdates: rdates.into(),
+        exdates: trr.exdates.iter().copied().collect(),
+        last: None,
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn rrule_series_next(trrs: &mut TempusRRuleSeries, out_zoned: *mut *mut TempusZoned) -> c_char {
+    match trrs.next() {
+        None => -1,
+        Some(zoned) => {
+            let tzoned = TempusZoned{zoned};
+            tzoned.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn free_rrule(trr: Box<TempusRRule>) -> c_longlong {
+    let raw = Box::into_raw(trr);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_rrule_series(trrs: Box<TempusRRuleSeries>) -> c_longlong {
+    let raw = Box::into_raw(trrs);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
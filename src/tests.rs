@@ -718,4 +718,816 @@ for t in start.series(Span.new().hours(3)) {
 21:00:00
 "#));
     assert!(output.status.success());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_datetime_with() {
+    let script = make_script(r#"
+dt := DateTime.new(2024, 3, 10, 8, 30)
+writestdout(dt.with().hour(17).minute(0).build().to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2024-03-10T17:00:00"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_zoned_with_disambiguation() {
+    let script = make_script(r#"
+zdt := Zoned.parse("2024-03-10T08:30:00-04:00[America/New_York]")
+gap := zdt.with().hour(2).minute(30).disambiguation(Disambiguation.Earlier).build()
+writestdout(gap.to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2024-03-10T01:30:00-05:00[America/New_York]"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_date_new_overflow() {
+    let script = make_script(r#"
+d1 := Date.new_overflow(2024, 2, 30, Overflow.Constrain)
+d2 := Date.new_overflow(2025, 1, 32, Overflow.Balance)
+writestdout(d1.to_string() " " d2.to_string())
+for fields in [[2025, -9223372036854775807 - 1, 1], [2025, 1, -9223372036854775807 - 1]] {
+    try {
+        Date.new_overflow(fields[1], fields[2], fields[3], Overflow.Balance)
+    } catch Error as e {
+        writestdout(" error")
+    }
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2024-02-29 2025-02-01 error error"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_datetime_new_overflow_balance() {
+    let script = make_script(r#"
+dt := DateTime.new_overflow(2024, 12, 31, 23, 75, 0, 0, Overflow.Balance)
+writestdout(dt.to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-01-01T00:15:00"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_date_checked_add_overflow() {
+    let script = make_script(r#"
+d := Date.new(2024, 1, 31)
+constrained := d.checked_add(Span.new().months(1))
+balanced := d.checked_add(Span.new().months(1), Overflow.Balance)
+writestdout(constrained.to_string() " " balanced.to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2024-02-29 2024-03-02"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_datetime_start_of_week() {
+    let script = make_script(r#"
+dt := DateTime.parse("2025-11-13T17:45:12")
+writestdout(dt.start_of(Unit.Week, WeekDay.Sunday).to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-11-09T00:00:00"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_zoned_start_of_day_gap() {
+    let script = make_script(r#"
+zdt := Zoned.parse("2015-10-18T12:00:00-02:00[America/Sao_Paulo]")
+writestdout(zdt.start_of(Unit.Day).to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2015-10-18T01:00:00-02:00[America/Sao_Paulo]"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_date_quarters() {
+    let script = make_script(r#"
+d := Date.parse("2024-11-30")
+writestdout(Format("{} {} {} {} {} {}", d.quarter(), d.first_of_quarter().to_string(), d.last_of_quarter().to_string(), d.checked_add_quarters(1).to_string(), Date.parse("2024-01-15").quarters_until(d), d.strftime("%Y-Q%q")))
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("4 2024-10-01 2024-12-31 2025-02-28 3 2024-Q4"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_zoned_first_of_quarter() {
+    let script = make_script(r#"
+zdt := Zoned.parse("2025-05-20T09:30:00-04:00[America/New_York]")
+writestdout(zdt.first_of_quarter().to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-04-01T09:30:00-04:00[America/New_York]"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_date_week_of_year() {
+    let script = make_script(r#"
+d := Date.parse("2024-12-31")
+jan := Date.parse("2022-01-01")
+writestdout(Format("{} {} {} {} {}", d.week_of_year(), d.week_year(), jan.week_of_year(WeekDay.Sunday, FirstWeek.FullWeek), jan.week_year(WeekDay.Sunday, FirstWeek.FullWeek), Date.parse("2025-11-13").week_of_month()))
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("1 2025 52 2021 3"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_date_weekday_utilities() {
+    let script = make_script(r#"
+d := Date.parse("2025-11-28")
+counts := Date.parse("2025-11-30").count_weekdays(Date.parse("2025-11-01"))
+writestdout(Format("{} {} {} {} {}", d.is_last_weekday_of_month(), d.weekday_of_month_ordinal(), d.days_until_weekday(WeekDay.Monday), counts[WeekDay.Saturday], counts[WeekDay.Friday]))
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("1 4 3 5 4"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_yearmonth() {
+    let script = make_script(r#"
+ym := YearMonth.parse("2024-12")
+next := ym.checked_add_months(3)
+writestdout(Format("{} {} {} {}", next.to_string(), YearMonth.new(2025, 2).last_of_month().to_string(), next.months_until(YearMonth.parse("2024-04")), next.gt(ym)))
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-03 2025-02-28 -11 1"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_monthday_to_date() {
+    let script = make_script(r#"
+md := MonthDay.parse("--02-29")
+writestdout(Format("{} {} {} {}", md.to_string(), md.to_date(2025).to_string(), md.to_date(2025, Overflow.Balance).to_string(), md.to_date(2024, Overflow.Reject).to_string()))
+try {
+    MonthDay.parse("--aéa")
+} catch Error as e {
+    writestdout(" error")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("--02-29 2025-02-28 2025-03-01 2024-02-29 error"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_daterange_set_operations() {
+    let script = make_script(r#"
+vacation := DateRange.new(Date.parse("2025-07-01"), Date.parse("2025-07-10"), true)
+trip := DateRange.new(Date.parse("2025-07-08"), Date.parse("2025-07-20"))
+writestdout(Format("{} {} {} {} {}", vacation.overlaps(trip), vacation.intersection(trip).to_string(), vacation.union(trip).to_string(), vacation.gap(trip) = "", vacation.length().to_string()))
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("1 [2025-07-08, 2025-07-11) [2025-07-01, 2025-07-20) 1 P10D"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_timestamprange_split() {
+    let script = make_script(r#"
+window := TimestampRange.new(Timestamp.parse("2025-01-01T00:00:00Z"), Timestamp.parse("2025-01-01T01:00:00Z"))
+for piece in window.split(Span.new().minutes(45)) {
+    writestdout(piece.to_string() " ")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("[2025-01-01T00:00:00Z, 2025-01-01T00:45:00Z) [2025-01-01T00:45:00Z, 2025-01-01T01:00:00Z) "));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_interval_parse() {
+    let script = make_script(r#"
+iv := Interval.parse("2025-01-01T00:00Z/P1M")
+ziv := Interval.parse("2024-03-09T12:00[America/New_York]/P1D")
+writestdout(Format("{} {} {}", iv.end().to_string(), iv.span().to_string(), ziv.end().to_string()))
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-02-01T00:00:00Z P1M 2024-03-10T12:00:00-04:00[America/New_York]"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_interval_repeating_series() {
+    let script = make_script(r#"
+meetings := Interval.parse("R3/2025-01-01T09:00Z/PT1H")
+writestdout(Format("{} {} ", meetings.repetitions(), meetings.to_string()))
+for meeting in meetings.series() {
+    writestdout(meeting.start().to_string() " ")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("3 R3/2025-01-01T09:00:00Z/PT1H 2025-01-01T09:00:00Z 2025-01-01T10:00:00Z 2025-01-01T11:00:00Z "));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_freebusy_find() {
+    let script = make_script(r#"
+fb := FreeBusy.new()
+ny := fb.add_participant("America/New_York", Time.parse("09:00"), Time.parse("17:00"))
+berlin := fb.add_participant("Europe/Berlin", Time.parse("09:00"), Time.parse("17:00"))
+fb.add_busy(Zoned.parse("2025-03-10T15:00[Europe/Berlin]"), Zoned.parse("2025-03-10T16:00[Europe/Berlin]"), berlin)
+slots := fb.find(Zoned.parse("2025-03-10T00:00[UTC]"), Zoned.parse("2025-03-11T00:00[UTC]"), SignedDuration.from_mins(30))
+writestdout(slots.Length " ")
+for slot in slots {
+    writestdout(slot.to_string() " ")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2 [2025-03-10T13:00:00Z, 2025-03-10T14:00:00Z) [2025-03-10T15:00:00Z, 2025-03-10T16:00:00Z) "));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_business_calendar() {
+    let script = make_script(r#"
+cal := BusinessCalendar.new()
+cal.add_holiday(Date.parse("2025-12-25"))
+cal.add_holiday(Date.parse("2025-12-26"))
+d := Date.parse("2025-12-24")
+writestdout(cal.add_business_days(d, 1).to_string() " ")
+writestdout(d.checked_add_business_days(-1, cal).to_string() " ")
+writestdout(cal.business_days_between(Date.parse("2025-12-01"), Date.parse("2026-01-01")) " ")
+writestdout(cal.is_business_day(Date.parse("2025-12-25")) " ")
+writestdout(cal.is_holiday(Date.parse("2025-12-26")) " ")
+writestdout(cal.roll(Date.parse("2025-05-31"), RollConvention.Following).to_string() " ")
+writestdout(cal.roll(Date.parse("2025-05-31"), RollConvention.ModifiedFollowing).to_string() " ")
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-12-29 2025-12-23 21 0 1 2025-06-02 2025-05-30 "));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_business_calendar_zoned() {
+    let script = make_script(r#"
+cal := BusinessCalendar.new([WeekDay.Saturday, WeekDay.Sunday])
+cal.add_holiday(Date.parse("2025-12-25"))
+writestdout(cal.add_business_days(Zoned.parse("2025-12-24T09:30[America/New_York]"), 2).to_string() " ")
+writestdout(cal.previous_business_day(Date.parse("2025-12-29")).to_string() " ")
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-12-29T09:30:00-05:00[America/New_York] 2025-12-26 "));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_holiday_rules_builtin() {
+    let script = make_script(r#"
+us := HolidayRules.builtin("US")
+holidays := us.holidays(2021)
+writestdout(holidays.Length " ")
+writestdout(holidays[12].date.to_string() " " holidays[12].name "|")
+uk := HolidayRules.builtin("UK")
+for holiday in uk.holidays(2022) {
+    if (holiday.date.month() = 12) {
+        writestdout(holiday.date.to_string() " " holiday.name "|")
+    }
+}
+writestdout(Date.easter(2025).to_string() " ")
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("12 2021-12-31 New Year's Day|2022-12-26 Boxing Day|2022-12-27 Christmas Day|2025-04-20 "));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_holiday_rules_business_calendar() {
+    let script = make_script(r#"
+rules := HolidayRules.parse("Founders' Day = 05/Fri#last nearest; Thanksgiving = 11/Thursday#4")
+writestdout(rules.holidays(2025)[1].date.to_string() " ")
+cal := BusinessCalendar.new().add_holiday_rules(rules)
+writestdout(cal.add_business_days(Date.parse("2025-11-26"), 1).to_string() " ")
+writestdout(cal.is_holiday(Date.parse("2025-05-30")) " ")
+writestdout(rules.is_holiday(Date.parse("2025-05-29")) " ")
+writestdout(StrSplit(rules.to_string(), "`n")[1])
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-05-30 2025-11-28 1 0 Founders' Day = 05/Fri#last nearest"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_working_hours() {
+    let script = make_script(r#"
+weekdays := [WeekDay.Monday, WeekDay.Tuesday, WeekDay.Wednesday, WeekDay.Thursday, WeekDay.Friday]
+hours := WorkingHours.new("America/New_York")
+hours.add_shift(weekdays, Time.parse("09:00"), Time.parse("12:00"))
+hours.add_shift(weekdays, Time.parse("13:00"), Time.parse("17:00"))
+hours.set_calendar(BusinessCalendar.new().add_holiday_rules(HolidayRules.builtin("US")))
+opened := Zoned.parse("2025-11-26T16:00[America/New_York]")
+closed := Zoned.parse("2025-12-01T10:30[America/New_York]")
+writestdout(hours.add(opened, SignedDuration.from_hours(2)).to_string() " ")
+writestdout(hours.duration_between(opened, closed).to_string() " ")
+writestdout(hours.duration_between(closed, opened).to_string() " ")
+writestdout(hours.add(Zoned.parse("2025-12-01T09:30[America/New_York]"), SignedDuration.from_hours(-1).checked_sub(SignedDuration.from_mins(30))).to_string() " ")
+writestdout(hours.is_working_time(Zoned.parse("2025-12-01T12:30[America/New_York]")) " ")
+writestdout(hours.is_working_time(Zoned.parse("2025-12-01T13:00[America/New_York]")))
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-11-28T10:00:00-05:00[America/New_York] PT9H30M -PT9H30M 2025-11-28T16:00:00-05:00[America/New_York] 0 1"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_fiscal_calendar_weeks() {
+    let script = make_script(r#"
+retail := FiscalCalendar.weeks(1, WeekDay.Saturday, FiscalYearEnd.NearestWeekday, 454, FiscalYearName.StartYear)
+fields := retail.fields(Date.parse("2024-03-03"))
+writestdout(fields.year " " fields.quarter " " fields.period " " fields.week "|")
+fields := retail.fields(Date.parse("2024-02-03"))
+writestdout(fields.year " " fields.quarter " " fields.period " " fields.week "|")
+writestdout(retail.weeks_in_year(2023) " " retail.weeks_in_year(2024) " ")
+writestdout(retail.year_range(2024).to_string() " ")
+writestdout(retail.period_range(2023, 12).to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2024 1 2 5|2023 4 12 53|53 52 [2024-02-04, 2025-02-02) [2023-12-31, 2024-02-04)"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_fiscal_calendar_weeks_ending_in_december() {
+    let script = make_script(r#"
+cal := FiscalCalendar.weeks(12, WeekDay.Saturday, FiscalYearEnd.NearestWeekday, 445, FiscalYearName.StartYear)
+for d in ["2023-12-30", "2023-12-31", "2024-12-29"] {
+    writestdout(cal.fields(Date.parse(d)).year " ")
+}
+writestdout("|" cal.year_range(2024).to_string() " " cal.year_range(2025).to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2023 2024 2025 |[2023-12-31, 2024-12-29) [2024-12-29, 2026-01-04)"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_fiscal_calendar_months() {
+    let script = make_script(r#"
+federal := FiscalCalendar.months(10)
+writestdout(federal.year(Date.parse("2024-10-01")) " ")
+d := Date.parse("2025-01-15")
+writestdout(federal.year(d) " " federal.quarter(d) " " federal.period(d) " ")
+writestdout(federal.quarter_range(2025, 2).to_string() " ")
+writestdout(federal.week_range(2025, 53).to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025 2025 2 4 [2025-01-01, 2025-04-01) [2025-09-30, 2025-10-01)"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_rrule_series() {
+    let script = make_script(r#"
+rule := RRule.parse("FREQ=MONTHLY;BYDAY=2TU,4TU;COUNT=4", Zoned.parse("2025-01-14T10:00[America/New_York]"))
+rule.add_exdate(Zoned.parse("2025-01-28T10:00[America/New_York]"))
+rule.add_rdate(Zoned.parse("2025-01-30T10:00[America/New_York]"))
+for z in rule.series() {
+    writestdout(z.to_string() " ")
+}
+writestdout("|" rule.to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-01-14T10:00:00-05:00[America/New_York] 2025-01-30T10:00:00-05:00[America/New_York] 2025-02-11T10:00:00-05:00[America/New_York] 2025-02-25T10:00:00-05:00[America/New_York] |FREQ=MONTHLY;COUNT=4;BYDAY=2TU,4TU"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_rrule_dst_and_setpos() {
+    let script = make_script(r#"
+rule := RRule.parse("FREQ=WEEKLY;BYDAY=MO,FR;UNTIL=20250310", Zoned.parse("2025-03-07T09:00[America/New_York]"))
+for z in rule.series() {
+    writestdout(z.to_string() " ")
+}
+writestdout("|")
+rule := RRule.parse("RRULE:FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;COUNT=3", Zoned.parse("2025-01-31T17:00[Europe/Berlin]"))
+for z in rule.series() {
+    writestdout(z.date().to_string() " ")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-03-07T09:00:00-05:00[America/New_York] 2025-03-10T09:00:00-04:00[America/New_York] |2025-01-31 2025-02-28 2025-03-31 "));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_cron_next_previous() {
+    let script = make_script(r#"
+job := Cron.parse("*/15 9-17 * * MON-FRI")
+now := Zoned.parse("2025-03-07T17:50-05[America/New_York]")
+writestdout(job.next(now).to_string() " " job.previous(now).to_string() " |")
+job := Cron.parse("0   9 * *  5L")
+z := Zoned.parse("2025-01-01T00:00[UTC]")
+loop 3 {
+    z := job.next(z)
+    writestdout(z.date().to_string() " ")
+}
+writestdout("|" job.to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-03-10T09:00:00-04:00[America/New_York] 2025-03-07T17:45:00-05:00[America/New_York] |2025-01-31 2025-02-28 2025-03-28 |0 9 * * 5L"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_cron_dst() {
+    let script = make_script(r#"
+job := Cron.parse("30 2 * * *")
+writestdout(job.next(Zoned.parse("2025-03-08T12:00-05[America/New_York]")).to_string() " ")
+job := Cron.parse("30 1 * * *")
+z := job.next(Zoned.parse("2025-11-01T12:00-04[America/New_York]"))
+writestdout(z.to_string() " " job.next(z).to_string() " |")
+job := Cron.parse("*/30 * * * *")
+z := Zoned.parse("2025-11-02T01:10-04[America/New_York]")
+loop 3 {
+    z := job.next(z)
+    writestdout(z.to_string() " ")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-03-09T03:00:00-04:00[America/New_York] 2025-11-02T01:30:00-04:00[America/New_York] 2025-11-03T01:30:00-05:00[America/New_York] |2025-11-02T01:30:00-04:00[America/New_York] 2025-11-02T01:00:00-05:00[America/New_York] 2025-11-02T01:30:00-05:00[America/New_York] "));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_cron_stepped_day_fields() {
+    let script = make_script(r#"
+job := Cron.parse("0 0 */2 * *")
+z := Zoned.parse("2025-01-01T00:00[UTC]")
+loop 3 {
+    z := job.next(z)
+    writestdout(z.date().to_string() " ")
+}
+writestdout("|")
+job := Cron.parse("0 0 */10 * MON")
+z := Zoned.parse("2025-01-01T00:00[UTC]")
+loop 3 {
+    z := job.next(z)
+    writestdout(z.date().to_string() " ")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-01-03 2025-01-05 2025-01-07 |2025-03-31 2025-04-21 2025-07-21 "));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_ical_property_parse() {
+    let script = make_script(r#"
+prop := IcalProperty.parse("DTSTART;TZID=Europe/Berlin:20250310T090000")
+writestdout(prop.value().to_string() " ")
+writestdout(IcalProperty.parse("DTSTART:20250310T090000").value().to_string() " ")
+writestdout(IcalProperty.parse("DTSTART:20250310T090000Z").value().to_string() " |")
+for d in IcalProperty.parse("EXDATE;VALUE=DATE:20250310,20250317").values() {
+    writestdout(d.to_string() " ")
+}
+writestdout("|" IcalProperty.parse("TRIGGER:-PT15M").value().to_string())
+writestdout(" |" prop.to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-03-10T09:00:00+01:00[Europe/Berlin] 2025-03-10T09:00:00 2025-03-10T09:00:00+00:00[UTC] |2025-03-10 2025-03-17 |-PT15M |DTSTART;TZID=Europe/Berlin:20250310T090000"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_ical_property_emit() {
+    let script = make_script(r#"
+writestdout(IcalProperty.new("DTEND").add(Zoned.parse("2025-03-10T10:30[America/New_York]")).to_string())
+writestdout(" |" IcalProperty.new("dtstart").add(Date.new(2025, 3, 10)).to_string())
+writestdout(" |" IcalProperty.new("DURATION").add(Span.new().days(1).hours(2)).to_string())
+writestdout(" |" IcalProperty.new("X-WAIT").add(Span.new().hours(1).seconds(5)).to_string())
+writestdout(" |" IcalProperty.new("DTSTAMP").add(Timestamp.parse("2025-03-10T14:30:00.5Z").in_tz("UTC")).to_string())
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("DTEND;TZID=America/New_York:20250310T103000 |DTSTART;VALUE=DATE:20250310 |DURATION:P1DT2H |X-WAIT;VALUE=DURATION:PT1H0M5S |DTSTAMP:20250310T143000Z"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_ahk_time_conversion() {
+    let script = make_script(r#"
+writestdout(DateTime.from_ahk_time("20250310143005").to_string() " ")
+writestdout(DateTime.from_ahk_time(2025031014).to_string() " ")
+writestdout(Date.from_ahk_time("2025").to_string() " |")
+writestdout(DateTime.parse("2025-03-10T14:30:05.75").to_ahk_time() " ")
+writestdout(Date.new(2025, 3, 10).to_ahk_time() " |")
+z := Zoned.from_ahk_time("20250309023000", "America/New_York")
+writestdout(z.to_string() " " z.to_ahk_time() " ")
+writestdout(Zoned.from_ahk_time("202503101430", Timezone.get("UTC")).to_string() " |")
+try {
+    DateTime.from_ahk_time("202503101")
+} catch Error as e {
+    writestdout("error")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-03-10T14:30:05 2025-03-10T14:00:00 2025-01-01 |20250310143005 20250310000000 |2025-03-09T03:30:00-04:00[America/New_York] 20250309033000 2025-03-10T14:30:00+00:00[UTC] |error"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_filetime_systemtime() {
+    let script = make_script(r#"
+writestdout(Timestamp.parse("2025-03-10T14:30:00Z").to_filetime() " ")
+writestdout(Timestamp.from_filetime(133860906000000001).to_string() " ")
+writestdout(Timestamp.from_filetime(0).to_string() " |")
+st := DateTime.parse("2025-03-10T14:30:00.2509").to_systemtime()
+loop 8 {
+    writestdout(NumGet(st, (A_Index - 1) * 2, "UShort") (A_Index < 8 ? " " : " |"))
+}
+writestdout(DateTime.from_systemtime(st).to_string() " |")
+try {
+    Timestamp.parse("1600-12-31T23:59:59Z").to_filetime()
+} catch Error as e {
+    writestdout("error ")
+}
+NumPut("UShort", 4295, st, 14)
+try {
+    DateTime.from_systemtime(st)
+} catch Error as e {
+    writestdout("error")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("133860906000000000 2025-03-10T14:30:00.0000001Z 1601-01-01T00:00:00Z |2025 3 1 10 14 30 0 250 |2025-03-10T14:30:00.25 |error error"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_ole_date_excel_serial() {
+    let script = make_script(r#"
+writestdout(DateTime.from_ole_date(-1.25).to_string() " ")
+writestdout(Format("{:.2f}", DateTime.parse("1899-12-29T06:00").to_ole_date()) " ")
+writestdout(DateTime.from_ole_date(45726.604166666664).to_string() " |")
+writestdout(Date.from_excel_serial(61).to_string() " ")
+writestdout(Format("{:.2f}", Date.parse("1900-02-28").to_excel_serial()) " ")
+writestdout(Date.from_excel_serial(44264, ExcelDateSystem.Date1904).to_string() " ")
+writestdout(Format("{:.2f}", DateTime.parse("2025-03-10T12:00").to_excel_serial(ExcelDateSystem.Date1904)) " |")
+try {
+    Date.from_excel_serial(60)
+} catch Error as e {
+    writestdout("error")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("1899-12-29T06:00:00 -1.25 2025-03-10T14:30:00 |1900-03-01 59.00 2025-03-10 44264.50 |error"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_dos_ntp() {
+    let script = make_script(r#"
+writestdout(DateTime.from_dos(23146, 29655).to_string() " ")
+dt := DateTime.parse("2025-03-10T14:30:47.5")
+writestdout(dt.to_dos_date() " " dt.to_dos_time() " |")
+ts := Timestamp.from_ntp(3950605800, 0x80000000)
+writestdout(ts.to_string() " " ts.to_ntp_seconds() " " ts.to_ntp_fraction() " " ts.ntp_era() " |")
+ts := Timestamp.from_ntp(0)
+writestdout(ts.to_string() " " ts.ntp_era() " |")
+try {
+    DateTime.parse("1979-12-31T23:59:59").to_dos_date()
+} catch Error as e {
+    writestdout("error ")
+}
+try {
+    Timestamp.from_ntp(0, 0, -2147483648)
+} catch Error as e {
+    writestdout("error")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-03-10T14:30:46 23146 29655 |2025-03-10T14:30:00.5Z 3950605800 2147483648 0 |2036-02-07T06:28:16Z 1 |error error"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_epoch_count_and_id_timestamps() {
+    let script = make_script(r#"
+ts := Timestamp.from_epoch_count(794860200, "2000-01-01T00:00:00Z")
+writestdout(ts.to_string() " " ts.count_since(Timestamp.parse("2000-01-01T00:00:00Z")) " ")
+writestdout(Timestamp.parse("1999-12-31T23:59:59.5Z").count_since("2000-01-01T00:00:00Z", Unit.Second) " |")
+writestdout(Timestamp.from_snowflake(175928847299117063, SnowflakeEpoch.Discord).to_string() " ")
+writestdout(Timestamp.from_uuid("C232AB00-9414-11EC-B3C8-9F6BDECED846").to_string() " ")
+writestdout(Timestamp.from_uuid("017f22e2-79b0-7cc3-98c4-dc0c0c07398f").to_string() " ")
+writestdout(Timestamp.from_ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV").to_string() " |")
+try {
+    Timestamp.from_uuid("919108f7-52d1-4320-9bac-f847db4148a8")
+} catch Error as e {
+    writestdout("error")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2025-03-09T18:30:00Z 794860200 -1 |2016-04-30T11:18:25.796Z 2022-02-22T19:22:22Z 2022-02-22T19:22:22Z 2016-07-30T23:54:10.259Z |error"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_julian_day_counts() {
+    let script = make_script(r#"
+d := Date.parse("2000-01-01")
+writestdout(d.to_julian_day() " " d.to_modified_julian_day() " " d.to_rata_die() " ")
+writestdout(Date.from_julian_day(0).to_string() " " Date.from_rata_die(1).to_string() " |")
+ts := Timestamp.parse("2000-01-01T12:00:00Z")
+writestdout(Format("{:.2f}", ts.to_julian_date()) " " Format("{:.2f}", ts.to_modified_julian_date()) " ")
+writestdout(Timestamp.from_julian_date(2460745.1041666665).to_string() " |")
+try {
+    Date.from_julian_day(1000000000000000000)
+} catch Error as e {
+    writestdout("error")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2451545 51544 730120 -004713-11-24 0001-01-01 |2451545.00 51544.50 2025-03-10T14:30:00Z |error"));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_gps_tai_leap_seconds() {
+    let script = make_script(r##"
+ts := Timestamp.parse("2017-01-01T00:00:00Z")
+writestdout(ts.to_tai().to_string() " " ts.to_tt().to_string() " ")
+ts := Timestamp.parse("2025-03-10T14:30:00.25Z")
+writestdout(ts.gps_week() " " Format("{:.2f}", ts.gps_seconds()) " ")
+writestdout(Timestamp.from_gps(2357, 138618.25).to_string() " |")
+start := Timestamp.parse("2016-12-31T23:59:59Z")
+end := Timestamp.parse("2017-01-01T00:00:00Z")
+writestdout(start.duration_until(end).to_string() " " start.elapsed_until(end).to_string() " |")
+table := LeapSecondTable.parse("#@`t3960057600`n2272060800`t10`t# 1 Jan 1972`n2287785600`t11`t# 1 Jul 1972`n3692217600`t37`t# 1 Jan 2017`n")
+writestdout(table.length() " " table.tai_offset(Timestamp.parse("2000-01-01T00:00:00Z")) " ")
+writestdout(Timestamp.parse("1972-07-01T00:00:00Z").to_tai(table).to_string() " |")
+try {
+    Timestamp.parse("1971-12-31T00:00:00Z").to_tai()
+} catch Error as e {
+    writestdout("error ")
+}
+for bad in ["-9223372036854775808`t10", "2272060800`t9223372036854775807"] {
+    try {
+        LeapSecondTable.parse(bad)
+    } catch Error as e {
+        writestdout("error ")
+    }
+}
+"##);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("2017-01-01T00:00:37 2017-01-01T00:01:09.184 2357 138618.25 2025-03-10T14:30:00.25Z |PT1S PT2S |3 11 1972-07-01T00:00:11 |error error error "));
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_calendar_date() {
+    let script = make_script(r#"
+d := Date.parse("2025-03-10")
+writestdout(d.in_calendar(Calendar.Julian).to_string() " |")
+writestdout(d.in_calendar(Calendar.Persian).to_string() " |")
+writestdout(CalendarDate.from_date(d, Calendar.Islamic).to_string() " |")
+hebrew := d.in_calendar(Calendar.Hebrew)
+writestdout(hebrew.day() " " hebrew.month_name() " " hebrew.year() " " hebrew.month() " " hebrew.months_in_year() " |")
+writestdout(CalendarDate.new(Calendar.Persian, 1404, 1, 1).to_date().to_string() " |")
+writestdout(CalendarDate.new(Calendar.Islamic, 1446, 9, 30).add_months(1).to_string() " ")
+writestdout(CalendarDate.new(Calendar.Hebrew, 5784, 6, 30).add_years(1).to_string() " ")
+writestdout(hebrew.add_days(4).add_years(-1).to_string() " |")
+try {
+    CalendarDate.new(Calendar.Hebrew, 5785, 13, 1)
+} catch Error as e {
+    writestdout("error")
+}
+"#);
+    let output = run_script(script);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr, "");
+    assert_eq!(stdout.to_string(), String::from("25 February 2025 |20 Esfand 1403 |10 Ramadan 1446 |10 Adar 5785 6 12 |2025-03-21 |29 Shawwal 1446 29 Adar 5785 14 Adar II 5784 |error"));
+    assert!(output.status.success());
+}
@@ -1,12 +1,12 @@
 use std::cmp::Ordering;
 use std::ffi::{c_char, c_int, c_longlong, c_short};
 use std::str::FromStr;
-use jiff::civil::{Time, TimeDifference, TimeRound, TimeSeries};
-use jiff::{Error};
+use jiff::civil::{Time, TimeDifference, TimeRound, TimeSeries, TimeWith};
+use jiff::{Error, RoundMode, Span, Unit};
 use crate::datetime::TempusDateTime;
 use crate::duration::TempusSignedDuration;
 use crate::span::TempusSpan;
-use crate::utils::{ahk_str_to_string, round_mode_from_i8, set_last_error_message, string_into_ahk_buff, unit_from_i8, AHKStringBuffer, AHKWstr};
+use crate::utils::{ahk_str_to_string, narrow_field, overflow_from_i8, period_from_i8, round_mode_from_i8, set_last_error_message, Overflow, Period, string_into_ahk_buff, unit_from_i8, AHKStringBuffer, AHKWstr};
 
 #[repr(C)]
 pub struct TempusTime {
@@ -18,6 +18,11 @@ pub struct TempusTimeSeries {
     pub series: TimeSeries
 }
 
+#[repr(C)]
+pub struct TempusTimeWith {
+    with: TimeWith
+}
+
 
 
 
@@ -109,6 +114,66 @@ pub extern "C" fn time_new(hour: i8, minute: i8, second: i8, subsec_nano: i32, o
     }
 }
 
+/// Resolves time fields under the given overflow mode. The returned day count is the number of
+/// days carried out of the time when balancing, and is always zero otherwise.
+pub(crate) fn time_from_fields(hour: i64, minute: i64, second: i64, subsec_nano: i64, overflow: Overflow) -> Result<(i64, Time), String> {
+    match overflow {
+        Overflow::Reject => {
+            let time = Time::new(
+                narrow_field(hour, "hour")?,
+                narrow_field(minute, "minute")?,
+                narrow_field(second, "second")?,
+                narrow_field(subsec_nano, "subsec_nanosecond")?,
+            ).map_err(|e| e.to_string())?;
+            Ok((0, time))
+        }
+        Overflow::Constrain => {
+            let time = Time::new(
+                hour.clamp(0, 23) as i8,
+                minute.clamp(0, 59) as i8,
+                second.clamp(0, 59) as i8,
+                subsec_nano.clamp(0, 999_999_999) as i32,
+            ).map_err(|e| e.to_string())?;
+            Ok((0, time))
+        }
+        Overflow::Balance => {
+            const NANOS_PER_DAY: i128 = 86_400 * 1_000_000_000;
+            let total = ((hour as i128 * 60 + minute as i128) * 60 + second as i128) * 1_000_000_000 + subsec_nano as i128;
+            let days = total.div_euclid(NANOS_PER_DAY) as i64;
+            let nanos = total.rem_euclid(NANOS_PER_DAY) as i64;
+            let time = Time::new(
+                (nanos / 3_600_000_000_000) as i8,
+                (nanos / 60_000_000_000 % 60) as i8,
+                (nanos / 1_000_000_000 % 60) as i8,
+                (nanos % 1_000_000_000) as i32,
+            ).map_err(|e| e.to_string())?;
+            Ok((days, time))
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn time_new_overflow(hour: i64, minute: i64, second: i64, subsec_nano: i64, overflow_i: i8, out_time: *mut *mut TempusTime) -> c_longlong {
+    let overflow = match overflow_from_i8(overflow_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(overflow) => overflow
+    };
+    match time_from_fields(hour, minute, second, subsec_nano, overflow) {
+        Err(e) => {
+            set_last_error_message(e);
+            -2
+        }
+        Ok((_, time)) => {
+            let ttime = TempusTime{time};
+            ttime.stuff_into(out_time);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn time_checked_add_span(tt: &TempusTime, other: &TempusSpan, out_time: *mut *mut TempusTime) -> c_longlong {
     match tt.time.checked_add(other.span) {
@@ -506,6 +571,157 @@ pub extern "C" fn time_series_next(tseries: &mut TempusTimeSeries, out_time: *mu
     }
 }
 
+/// Returns the first instant of the period containing the given time. Only periods of a day or
+/// shorter are supported.
+pub(crate) fn time_start_of_period(time: Time, period: Period) -> Result<Time, String> {
+    match period {
+        Period::Unit(Unit::Day) => Ok(Time::midnight()),
+        Period::Unit(unit) if period.is_time() => {
+            let rounder = TimeRound::new().smallest(unit).mode(RoundMode::Trunc);
+            time.round(rounder).map_err(|e| e.to_string())
+        }
+        _ => Err("unit must be Day or smaller for Time".to_string())
+    }
+}
+
+/// Returns the last nanosecond of the period containing the given time. Only periods of a day
+/// or shorter are supported.
+pub(crate) fn time_end_of_period(time: Time, period: Period) -> Result<Time, String> {
+    let start = time_start_of_period(time, period)?;
+    if period == Period::Unit(Unit::Day) {
+        return Ok(Time::MAX)
+    }
+    Ok(start.wrapping_add(period.span()).wrapping_sub(Span::new().nanoseconds(1)))
+}
+
+#[no_mangle]
+pub extern "C" fn time_truncate(tt: &TempusTime, unit_i: i8, out_time: *mut *mut TempusTime) -> c_longlong {
+    let unit = match unit_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(unit) => unit
+    };
+    match time_start_of_period(tt.time, Period::Unit(unit)) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(time) => {
+            let new_tt = TempusTime{time};
+            new_tt.stuff_into(out_time);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn time_start_of(tt: &TempusTime, unit_i: i8, out_time: *mut *mut TempusTime) -> c_longlong {
+    let period = match period_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(period) => period
+    };
+    match time_start_of_period(tt.time, period) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(time) => {
+            let new_tt = TempusTime{time};
+            new_tt.stuff_into(out_time);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn time_end_of(tt: &TempusTime, unit_i: i8, out_time: *mut *mut TempusTime) -> c_longlong {
+    let period = match period_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(period) => period
+    };
+    match time_end_of_period(tt.time, period) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(time) => {
+            let new_tt = TempusTime{time};
+            new_tt.stuff_into(out_time);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn time_with(tt: &TempusTime) -> Box<TempusTimeWith> {
+    Box::new(TempusTimeWith{with: tt.time.with()})
+}
+
+#[no_mangle]
+pub extern "C" fn time_with_hour(ttw: &mut TempusTimeWith, hour: i8) -> c_longlong {
+    ttw.with = ttw.with.hour(hour);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn time_with_minute(ttw: &mut TempusTimeWith, minute: i8) -> c_longlong {
+    ttw.with = ttw.with.minute(minute);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn time_with_second(ttw: &mut TempusTimeWith, second: i8) -> c_longlong {
+    ttw.with = ttw.with.second(second);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn time_with_millisecond(ttw: &mut TempusTimeWith, millisecond: i16) -> c_longlong {
+    ttw.with = ttw.with.millisecond(millisecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn time_with_microsecond(ttw: &mut TempusTimeWith, microsecond: i16) -> c_longlong {
+    ttw.with = ttw.with.microsecond(microsecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn time_with_nanosecond(ttw: &mut TempusTimeWith, nanosecond: i16) -> c_longlong {
+    ttw.with = ttw.with.nanosecond(nanosecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn time_with_subsec_nanosecond(ttw: &mut TempusTimeWith, subsec_nanosecond: i32) -> c_longlong {
+    ttw.with = ttw.with.subsec_nanosecond(subsec_nanosecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn time_with_build(ttw: &TempusTimeWith, out_time: *mut *mut TempusTime) -> c_longlong {
+    match ttw.with.build() {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(time) => {
+            let new_tt = TempusTime{time};
+            new_tt.stuff_into(out_time);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn free_time(time: Box<TempusTime>) -> c_longlong {
     let raw = Box::into_raw(time);
@@ -522,4 +738,13 @@ pub extern "C" fn free_time_series(timeseries: Box<TempusTimeSeries>) -> c_longl
         drop(Box::from_raw(raw))
     }
     0
-}
\ No newline at end of file
+}
+
+#[no_mangle]
+pub extern "C" fn free_time_with(ttw: Box<TempusTimeWith>) -> c_longlong {
+    let raw = Box::into_raw(ttw);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
@@ -1,16 +1,17 @@
 #![allow(dead_code)]
 
 use std::cmp::Ordering;
-use jiff::{Error, Timestamp, TimestampDifference, TimestampRound, TimestampSeries};
+use jiff::{Error, SignedDuration, Timestamp, TimestampDifference, TimestampRound, TimestampSeries};
 
 use std::ffi::{c_char, c_int, c_longlong};
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use jiff::fmt::strtime::BrokenDownTime;
+use jiff::tz::TimeZone;
 use crate::duration::TempusSignedDuration;
 use crate::span::TempusSpan;
 use crate::tz::TempusTimeZone;
-use crate::utils::{AHKWstr, ahk_str_to_string, set_last_error_message, string_into_ahk_buff, AHKStringBuffer, unit_from_i8, round_mode_from_i8};
+use crate::utils::{expand_quarter_directive, AHKWstr, ahk_str_to_string, set_last_error_message, string_into_ahk_buff, AHKStringBuffer, unit_from_i8, round_mode_from_i8, epoch_unit_nanoseconds, ulid_timestamp, uuid_timestamp};
 use crate::zoned::TempusZoned;
 
 
@@ -148,6 +149,252 @@ pub extern "C" fn timestamp_from_microsecond(s: i64, out_ts: *mut *mut TempusTim
     }
 }
 
+/// 100-nanosecond ticks from 1601-01-01, the epoch of Windows FILETIME, to 1970-01-01.
+const FILETIME_UNIX_EPOCH: i64 = 116_444_736_000_000_000;
+
+/// A Windows FILETIME (a count of 100-nanosecond ticks since 1601-01-01 UTC), as a 64-bit integer.
+#[no_mangle]
+pub extern "C" fn timestamp_from_filetime(ticks: i64, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    if ticks < 0 {
+        set_last_error_message(format!("FILETIME {ticks} is negative"));
+        return -1
+    }
+    let since_unix_epoch = ticks - FILETIME_UNIX_EPOCH;
+    let seconds = since_unix_epoch.div_euclid(10_000_000);
+    let nanoseconds = since_unix_epoch.rem_euclid(10_000_000) as i32 * 100;
+    match Timestamp::new(seconds, nanoseconds) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(ts) => {
+            TempusTimestamp{ts}.stuff_into(out_ts);
+            0
+        }
+    }
+}
+
+/// The timestamp as a Windows FILETIME. Nanoseconds that don't make up a whole tick are dropped.
+#[no_mangle]
+pub extern "C" fn timestamp_to_filetime(tts: &TempusTimestamp, out_ticks: &mut i64) -> c_longlong {
+    let ticks = tts.ts.as_nanosecond().div_euclid(100) + FILETIME_UNIX_EPOCH as i128;
+    if ticks < 0 {
+        set_last_error_message(format!("{} is before 1601, which FILETIME can't represent", tts.ts));
+        return -1
+    }
+    *out_ticks = ticks as i64;
+    0
+}
+
+/// Seconds from 1900-01-01, the NTP prime epoch, to 1970-01-01.
+pub(crate) const NTP_UNIX_EPOCH: i64 = 2_208_988_800;
+
+/// An NTP timestamp: the 32-bit seconds and fraction fields, and the era (each era is 2^32 seconds, era 0 starting
+/// 1900-01-01 UTC). The fraction is rounded to the nearest nanosecond.
+#[no_mangle]
+pub extern "C" fn timestamp_from_ntp(seconds: i64, fraction: i64, era: i32, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    if !(0..1 << 32).contains(&seconds) {
+        set_last_error_message(format!("NTP seconds {seconds} is out of range 0..=4294967295"));
+        return -1
+    }
+    if !(0..1 << 32).contains(&fraction) {
+        set_last_error_message(format!("NTP fraction {fraction} is out of range 0..=4294967295"));
+        return -2
+    }
+    let nanoseconds = ((fraction as i128 * 1_000_000_000 + (1 << 31)) >> 32) as i64;
+    let seconds = ((era as i128) << 32) + seconds as i128 - NTP_UNIX_EPOCH as i128;
+    let ts = i64::try_from(seconds).map_err(|_| format!("NTP era {era} is out of range")).and_then(|seconds| {
+        Timestamp::new(seconds, 0).and_then(|ts| ts.checked_add(SignedDuration::from_nanos(nanoseconds))).map_err(|e| e.to_string())
+    });
+    match ts {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(ts) => {
+            TempusTimestamp{ts}.stuff_into(out_ts);
+            0
+        }
+    }
+}
+
+/// The timestamp as NTP era, seconds and fraction fields. Nanoseconds are truncated to the fraction's resolution
+/// (about 0.23ns), so converting back gives the same timestamp.
+#[no_mangle]
+pub extern "C" fn timestamp_to_ntp(tts: &TempusTimestamp, out_era: &mut i32, out_seconds: &mut i64, out_fraction: &mut i64) -> c_longlong {
+    let since_prime_epoch = tts.ts.as_nanosecond().div_euclid(1_000_000_000) as i64 + NTP_UNIX_EPOCH;
+    let nanoseconds = tts.ts.as_nanosecond().rem_euclid(1_000_000_000) as i64;
+    *out_era = (since_prime_epoch >> 32) as i32;
+    *out_seconds = since_prime_epoch & 0xFFFF_FFFF;
+    *out_fraction = (nanoseconds << 32) / 1_000_000_000;
+    0
+}
+
+/// The timestamp `count` units after `epoch`. The unit can be anything up to days, which are taken as 24 hours.
+#[no_mangle]
+pub extern "C" fn timestamp_from_epoch_count(count: i64, epoch: &TempusTimestamp, unit_i: i8, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    let unit_nanoseconds = match unit_from_i8(unit_i).and_then(epoch_unit_nanoseconds) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(nanoseconds) => nanoseconds
+    };
+    match Timestamp::from_nanosecond(epoch.ts.as_nanosecond() + count as i128 * unit_nanoseconds) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(ts) => {
+            TempusTimestamp{ts}.stuff_into(out_ts);
+            0
+        }
+    }
+}
+
+/// The number of whole units from `epoch` to the timestamp, rounded toward the past, so that converting back with
+/// `timestamp_from_epoch_count` gives the start of the unit the timestamp is in.
+#[no_mangle]
+pub extern "C" fn timestamp_count_since_epoch(tts: &TempusTimestamp, epoch: &TempusTimestamp, unit_i: i8, out_count: &mut i64) -> c_longlong {
+    let unit_nanoseconds = match unit_from_i8(unit_i).and_then(epoch_unit_nanoseconds) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(nanoseconds) => nanoseconds
+    };
+    let count = (tts.ts.as_nanosecond() - epoch.ts.as_nanosecond()).div_euclid(unit_nanoseconds);
+    match i64::try_from(count) {
+        Err(_) => {
+            set_last_error_message(format!("the count from {} to {} doesn't fit in 64 bits", epoch.ts, tts.ts));
+            -2
+        }
+        Ok(count) => {
+            *out_count = count;
+            0
+        }
+    }
+}
+
+/// The time embedded in a Twitter-style snowflake ID: the bits above the lowest 22 count milliseconds since
+/// `epoch_millisecond` (a Unix time in milliseconds, as each service picks its own epoch).
+#[no_mangle]
+pub extern "C" fn timestamp_from_snowflake(id: i64, epoch_millisecond: i64, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    if id < 0 {
+        set_last_error_message(format!("snowflake ID {id} is negative"));
+        return -1
+    }
+    let Some(millisecond) = (id >> 22).checked_add(epoch_millisecond) else {
+        set_last_error_message(format!("snowflake ID {id} with epoch {epoch_millisecond} is out of range"));
+        return -2
+    };
+    match Timestamp::from_millisecond(millisecond) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -3
+        }
+        Ok(ts) => {
+            TempusTimestamp{ts}.stuff_into(out_ts);
+            0
+        }
+    }
+}
+
+/// The time embedded in a UUID of version 1, 6 or 7.
+#[no_mangle]
+pub extern "C" fn timestamp_from_uuid(ahk_uuid: AHKWstr, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    match ahk_str_to_string(ahk_uuid) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(uuid) => {
+            match uuid_timestamp(&uuid) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(ts) => {
+                    TempusTimestamp{ts}.stuff_into(out_ts);
+                    0
+                }
+            }
+        }
+    }
+}
+
+/// The time embedded in a ULID.
+#[no_mangle]
+pub extern "C" fn timestamp_from_ulid(ahk_ulid: AHKWstr, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    match ahk_str_to_string(ahk_ulid) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(ulid) => {
+            match ulid_timestamp(&ulid) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(ts) => {
+                    TempusTimestamp{ts}.stuff_into(out_ts);
+                    0
+                }
+            }
+        }
+    }
+}
+
+
+// The Julian Date and Modified Julian Date of 1970-01-01T00:00:00Z
+const JULIAN_DATE_UNIX_EPOCH: f64 = 2_440_587.5;
+const MODIFIED_JULIAN_DATE_UNIX_EPOCH: f64 = 40_587.0;
+const MILLISECONDS_PER_DAY: f64 = 86_400_000.0;
+
+/// The timestamp `days` (with the time of day as the fraction) into a day count in which 1970-01-01T00:00:00Z is
+/// `unix_epoch_day`, to the nearest millisecond.
+fn timestamp_from_fractional_day(days: f64, unix_epoch_day: f64, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    if !days.is_finite() {
+        set_last_error_message(format!("{days} is not a finite number"));
+        return -1
+    }
+    match Timestamp::from_millisecond(((days - unix_epoch_day) * MILLISECONDS_PER_DAY).round() as i64) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(ts) => {
+            TempusTimestamp{ts}.stuff_into(out_ts);
+            0
+        }
+    }
+}
+
+/// Reads a Julian Date: days since noon UTC on 4714-11-24 BCE (proleptic Gregorian), with the time as the fraction.
+/// A double only holds a Julian Date to about 40 microseconds, so the result is rounded to the nearest millisecond.
+#[no_mangle]
+pub extern "C" fn timestamp_from_julian_date(days: f64, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    timestamp_from_fractional_day(days, JULIAN_DATE_UNIX_EPOCH, out_ts)
+}
+
+#[no_mangle]
+pub extern "C" fn timestamp_to_julian_date(tts: &TempusTimestamp) -> f64 {
+    tts.ts.as_millisecond() as f64 / MILLISECONDS_PER_DAY + JULIAN_DATE_UNIX_EPOCH
+}
+
+/// Reads a Modified Julian Date: days since midnight UTC on 1858-11-17, with the time as the fraction. The result is
+/// rounded to the nearest millisecond.
+#[no_mangle]
+pub extern "C" fn timestamp_from_modified_julian_date(days: f64, out_ts: *mut *mut TempusTimestamp) -> c_longlong {
+    timestamp_from_fractional_day(days, MODIFIED_JULIAN_DATE_UNIX_EPOCH, out_ts)
+}
+
+#[no_mangle]
+pub extern "C" fn timestamp_to_modified_julian_date(tts: &TempusTimestamp) -> f64 {
+    tts.ts.as_millisecond() as f64 / MILLISECONDS_PER_DAY + MODIFIED_JULIAN_DATE_UNIX_EPOCH
+}
+
 
 #[no_mangle]
 pub extern "C" fn timestamp_now() -> Box<TempusTimestamp> {
@@ -174,6 +421,7 @@ pub extern "C" fn timestamp_strftime_length(tts: &TempusTimestamp, ahk_format_st
             -1
         }
         Ok(format_str) => {
+            let format_str = expand_quarter_directive(&format_str, TimeZone::UTC.to_datetime(tts.ts).month());
             let bdt = BrokenDownTime::from(tts.ts);
             let mut buf = String::new();
             match bdt.format(format_str, &mut buf) {
@@ -207,6 +455,7 @@ pub extern "C" fn timestamp_strftime(tts: &TempusTimestamp, ahk_format_str: AHKW
             -1
         }
         Ok(format_str) => {
+            let format_str = expand_quarter_directive(&format_str, TimeZone::UTC.to_datetime(tts.ts).month());
             let bdt = BrokenDownTime::from(tts.ts);
             let mut buf = String::new();
             match bdt.format(format_str, &mut buf) {
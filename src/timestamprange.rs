@@ -0,0 +1,240 @@
+use std::ffi::{c_char, c_longlong};
+use jiff::Span;
+use jiff::Timestamp;
+use crate::duration::TempusSignedDuration;
+use crate::timestamp::TempusTimestamp;
+use crate::range::{HalfOpen, RangeSplit};
+use crate::span::TempusSpan;
+use crate::utils::{set_last_error_message, string_into_ahk_buff, AHKStringBuffer};
+
+/// A range of timestamps. Closed ranges are stored as half-open ranges ending a nanosecond after their last instant.
+#[repr(C)]
+pub struct TempusTimestampRange {
+    pub range: HalfOpen<Timestamp>
+}
+
+impl TempusTimestampRange {
+    pub fn stuff_into(self, pointer: *mut *mut TempusTimestampRange) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+impl std::fmt::Display for TempusTimestampRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {})", self.range.start, self.range.end)
+    }
+}
+
+#[repr(C)]
+pub struct TempusTimestampRangeSeries {
+    split: RangeSplit<Timestamp>
+}
+
+impl TempusTimestampRangeSeries {
+    pub fn stuff_into(self, pointer: *mut *mut TempusTimestampRangeSeries) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+/// Creates a range from `start` to `end`. When `closed_i` is 1, `end` is included in the range; when it's 0, the
+/// range is half-open and stops just before `end`.
+#[no_mangle]
+pub extern "C" fn timestamprange_new(start: &TempusTimestamp, end: &TempusTimestamp, closed_i: i8, out_range: *mut *mut TempusTimestampRange) -> c_longlong {
+    let end = match closed_i {
+        0 => end.ts,
+        1 => {
+            match end.ts.checked_add(Span::new().nanoseconds(1)) {
+                Err(e) => {
+                    set_last_error_message(e.to_string());
+                    return -2
+                }
+                Ok(end) => end
+            }
+        }
+        _ => {
+            set_last_error_message("invalid options".to_string());
+            return -1
+        }
+    };
+    match HalfOpen::new(start.ts, end) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(range) => {
+            let new_range = TempusTimestampRange{range};
+            new_range.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_start(tr: &TempusTimestampRange) -> Box<TempusTimestamp> {
+    Box::new(TempusTimestamp{ts: tr.range.start})
+}
+
+/// The end of the range, which is not itself in the range.
+#[no_mangle]
+pub extern "C" fn timestamprange_end(tr: &TempusTimestampRange) -> Box<TempusTimestamp> {
+    Box::new(TempusTimestamp{ts: tr.range.end})
+}
+
+/// The last nanosecond in the range.
+#[no_mangle]
+pub extern "C" fn timestamprange_last(tr: &TempusTimestampRange, out_last: *mut *mut TempusTimestamp) -> c_longlong {
+    if tr.range.is_empty() {
+        set_last_error_message("an empty range has no last value".to_string());
+        return -1
+    }
+    match tr.range.end.checked_sub(Span::new().nanoseconds(1)) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(ts) => {
+            let last = TempusTimestamp{ts};
+            last.stuff_into(out_last);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_is_empty(tr: &TempusTimestampRange) -> c_char {
+    tr.range.is_empty() as i8
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_contains(tr: &TempusTimestampRange, value: &TempusTimestamp) -> c_char {
+    tr.range.contains(value.ts) as i8
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_contains_range(tr: &TempusTimestampRange, other: &TempusTimestampRange) -> c_char {
+    tr.range.contains_range(&other.range) as i8
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_overlaps(tr: &TempusTimestampRange, other: &TempusTimestampRange) -> c_char {
+    tr.range.overlaps(&other.range) as i8
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_eq(tr: &TempusTimestampRange, other: &TempusTimestampRange) -> c_char {
+    (tr.range == other.range) as i8
+}
+
+/// Returns 1, without setting a range, when the ranges don't overlap.
+#[no_mangle]
+pub extern "C" fn timestamprange_intersection(tr: &TempusTimestampRange, other: &TempusTimestampRange, out_range: *mut *mut TempusTimestampRange) -> c_longlong {
+    match tr.range.intersection(&other.range) {
+        None => {
+            1
+        }
+        Some(range) => {
+            let new_range = TempusTimestampRange{range};
+            new_range.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+/// Returns 1, without setting a range, when the ranges neither overlap nor touch.
+#[no_mangle]
+pub extern "C" fn timestamprange_union(tr: &TempusTimestampRange, other: &TempusTimestampRange, out_range: *mut *mut TempusTimestampRange) -> c_longlong {
+    match tr.range.union(&other.range) {
+        None => {
+            1
+        }
+        Some(range) => {
+            let new_range = TempusTimestampRange{range};
+            new_range.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+/// Returns 1, without setting a range, when there is no gap between the ranges.
+#[no_mangle]
+pub extern "C" fn timestamprange_gap(tr: &TempusTimestampRange, other: &TempusTimestampRange, out_range: *mut *mut TempusTimestampRange) -> c_longlong {
+    match tr.range.gap(&other.range) {
+        None => {
+            1
+        }
+        Some(range) => {
+            let new_range = TempusTimestampRange{range};
+            new_range.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_length(tr: &TempusTimestampRange) -> Box<TempusSignedDuration> {
+    Box::new(TempusSignedDuration{duration: tr.range.start.duration_until(tr.range.end)})
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_split(tr: &TempusTimestampRange, tspan: &TempusSpan, out_series: *mut *mut TempusTimestampRangeSeries) -> c_longlong {
+    match RangeSplit::new(tr.range, tspan.span, |value: Timestamp, span: Span| value.checked_add(span)) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(split) => {
+            let series = TempusTimestampRangeSeries{split};
+            series.stuff_into(out_series);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_series_next(trs: &mut TempusTimestampRangeSeries, out_range: *mut *mut TempusTimestampRange) -> c_char {
+    match trs.split.next() {
+        None => {
+            -1
+        }
+        Some(range) => {
+            let new_range = TempusTimestampRange{range};
+            new_range.stuff_into(out_range);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_string_length(tr: &TempusTimestampRange) -> usize {
+    tr.to_string().len()
+}
+
+#[no_mangle]
+pub extern "C" fn timestamprange_to_string(tr: &TempusTimestampRange, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(tr.to_string(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_timestamprange(tr: Box<TempusTimestampRange>) -> c_longlong {
+    let raw = Box::into_raw(tr);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn free_timestamprange_series(trs: Box<TempusTimestampRangeSeries>) -> c_longlong {
+    let raw = Box::into_raw(trs);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
@@ -5,7 +5,9 @@ use std::ffi::{c_char};
 use std::borrow::BorrowMut;
 use std::ptr;
 use std::sync::{Mutex, Once};
-use jiff::{RoundMode, Unit};
+use jiff::{RoundMode, SignedDuration, Span, Timestamp, Unit};
+use jiff::civil::{DateTime, Era};
+use jiff::tz::Disambiguation;
 
 static mut STD_ONCE_COUNTER: Option<Mutex<String>> = None;
 static INIT: Once = Once::new();
@@ -124,6 +126,457 @@ pub fn unit_from_i8(i: i8) -> Result<Unit, String> {
     }
 }
 
+pub fn era_from_i8(i: i8) -> Result<Era, String> {
+    match i {
+        -1 => Ok(Era::BCE),
+        1 => Ok(Era::CE),
+        _ => {
+            Err(format!("invalid era: {i}. Must be -1 (BCE) or 1 (CE)"))
+        }
+    }
+}
+
+pub fn disambiguation_from_i8(i: i8) -> Result<Disambiguation, String> {
+    match i {
+        1 => Ok(Disambiguation::Compatible),
+        2 => Ok(Disambiguation::Earlier),
+        3 => Ok(Disambiguation::Later),
+        4 => Ok(Disambiguation::Reject),
+        _ => {
+            Err(format!("invalid disambiguation: {i}. Must be in range 1-4 (inclusive)"))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    Reject,
+    Constrain,
+    Balance,
+}
+
+pub fn overflow_from_i8(i: i8) -> Result<Overflow, String> {
+    match i {
+        1 => Ok(Overflow::Reject),
+        2 => Ok(Overflow::Constrain),
+        3 => Ok(Overflow::Balance),
+        _ => {
+            Err(format!("invalid overflow mode: {i}. Must be in range 1-3 (inclusive)"))
+        }
+    }
+}
+
+pub fn narrow_field<T: TryFrom<i64>>(value: i64, name: &str) -> Result<T, String> {
+    T::try_from(value).map_err(|_| format!("parameter '{name}' with value {value} is not in the required range"))
+}
+
+/// A unit of time that something can be truncated to, or the start or end of which can be found.
+/// This is a superset of [`Unit`] that also allows for quarters and half-years.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Unit(Unit),
+    Quarter,
+    HalfYear,
+}
+
+impl Period {
+    /// The span covering exactly one of this period.
+    pub fn span(self) -> Span {
+        match self {
+            Period::Unit(Unit::Nanosecond) => Span::new().nanoseconds(1),
+            Period::Unit(Unit::Microsecond) => Span::new().microseconds(1),
+            Period::Unit(Unit::Millisecond) => Span::new().milliseconds(1),
+            Period::Unit(Unit::Second) => Span::new().seconds(1),
+            Period::Unit(Unit::Minute) => Span::new().minutes(1),
+            Period::Unit(Unit::Hour) => Span::new().hours(1),
+            Period::Unit(Unit::Day) => Span::new().days(1),
+            Period::Unit(Unit::Week) => Span::new().weeks(1),
+            Period::Unit(Unit::Month) => Span::new().months(1),
+            Period::Unit(Unit::Year) => Span::new().years(1),
+            Period::Quarter => Span::new().months(3),
+            Period::HalfYear => Span::new().months(6),
+        }
+    }
+
+    /// Whether this period is shorter than a day.
+    pub fn is_time(self) -> bool {
+        matches!(self, Period::Unit(unit) if unit < Unit::Day)
+    }
+}
+
+/// Accepts the same values as [`unit_from_i8`] plus `10` for quarters and `11` for half-years.
+pub fn period_from_i8(i: i8) -> Result<Period, String> {
+    match i {
+        10 => Ok(Period::Quarter),
+        11 => Ok(Period::HalfYear),
+        _ => {
+            match unit_from_i8(i) {
+                Ok(unit) => Ok(Period::Unit(unit)),
+                Err(_) => Err(format!("invalid unit: {i}. Must be in range 0-11 (inclusive)"))
+            }
+        }
+    }
+}
+
+
+/// The rule for which week is the first of the year (or month), given as the minimum number of days of the new
+/// year that week 1 must contain: `1` for the week containing January 1, `4` for ISO 8601, `7` for the first full week.
+pub fn first_week_from_i8(i: i8) -> Result<i8, String> {
+    match i {
+        1..=7 => Ok(i),
+        _ => {
+            Err(format!("invalid first week rule: {i}. Must be in range 1-7 (inclusive)"))
+        }
+    }
+}
+
+/// How a date that isn't a business day is moved onto one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RollConvention {
+    Following,
+    ModifiedFollowing,
+    Preceding,
+    ModifiedPreceding,
+}
+
+pub fn roll_convention_from_i8(i: i8) -> Result<RollConvention, String> {
+    match i {
+        1 => Ok(RollConvention::Following),
+        2 => Ok(RollConvention::ModifiedFollowing),
+        3 => Ok(RollConvention::Preceding),
+        4 => Ok(RollConvention::ModifiedPreceding),
+        _ => {
+            Err(format!("invalid roll convention: {i}. Must be in range 1-4 (inclusive)"))
+        }
+    }
+}
+
+/// How a 52/53-week fiscal year's last day is chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FiscalYearEnd {
+    /// The last given weekday of the year-end month.
+    LastWeekday,
+    /// The given weekday nearest to the last day of the year-end month.
+    NearestWeekday,
+}
+
+pub fn fiscal_year_end_from_i8(i: i8) -> Result<FiscalYearEnd, String> {
+    match i {
+        1 => Ok(FiscalYearEnd::LastWeekday),
+        2 => Ok(FiscalYearEnd::NearestWeekday),
+        _ => {
+            Err(format!("invalid fiscal year end rule: {i}. Must be in range 1-2 (inclusive)"))
+        }
+    }
+}
+
+/// Which calendar year a fiscal year is named after.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FiscalYearName {
+    EndYear,
+    StartYear,
+}
+
+pub fn fiscal_year_name_from_i8(i: i8) -> Result<FiscalYearName, String> {
+    match i {
+        1 => Ok(FiscalYearName::EndYear),
+        2 => Ok(FiscalYearName::StartYear),
+        _ => {
+            Err(format!("invalid fiscal year naming: {i}. Must be in range 1-2 (inclusive)"))
+        }
+    }
+}
+
+/// Which date system an Excel workbook uses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExcelDateSystem {
+    /// Serial 1 is 1900-01-01, and serial 60 is the 1900-02-29 that Excel has for compatibility with Lotus 1-2-3.
+    Date1900,
+    /// Serial 0 is 1904-01-01 (older Excel for Mac).
+    Date1904,
+}
+
+pub fn excel_date_system_from_i8(i: i8) -> Result<ExcelDateSystem, String> {
+    match i {
+        1 => Ok(ExcelDateSystem::Date1900),
+        2 => Ok(ExcelDateSystem::Date1904),
+        _ => {
+            Err(format!("invalid Excel date system: {i}. Must be in range 1-2 (inclusive)"))
+        }
+    }
+}
+
+/// A calendar other than the (proleptic) Gregorian one that [`jiff::civil::Date`] uses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Calendar {
+    /// The proleptic Julian calendar, with astronomical year numbering (1 BCE is year 0).
+    Julian,
+    /// The Solar Hijri calendar of Iran and Afghanistan, by the 33-year arithmetic rule.
+    Persian,
+    /// The tabular Islamic calendar, with the civil (Friday) epoch and leap years 2, 5, 7, 10, 13, 16, 18, 21, 24, 26
+    /// and 29 of each 30-year cycle.
+    Islamic,
+    /// The Hebrew calendar, with months counted from Tishrei.
+    Hebrew,
+}
+
+pub fn calendar_from_i8(i: i8) -> Result<Calendar, String> {
+    match i {
+        1 => Ok(Calendar::Julian),
+        2 => Ok(Calendar::Persian),
+        3 => Ok(Calendar::Islamic),
+        4 => Ok(Calendar::Hebrew),
+        _ => {
+            Err(format!("invalid calendar: {i}. Must be in range 1-4 (inclusive)"))
+        }
+    }
+}
+
+/// The quarter of the year (1-4) that a month (1-12) falls in.
+pub fn quarter_of_month(month: i8) -> i8 {
+    (month - 1) / 3 + 1
+}
+
+/// The half of the year (1-2) that a month (1-12) falls in.
+pub fn half_year_of_month(month: i8) -> i8 {
+    (month - 1) / 6 + 1
+}
+
+/// A span of the given number of quarters, expressed in months.
+pub fn quarters_to_span(quarters: i64) -> Result<Span, String> {
+    quarters.checked_mul(3)
+        .and_then(|months| Span::new().try_months(months).ok())
+        .ok_or_else(|| format!("parameter 'quarters' with value {quarters} is not in the required range"))
+}
+
+/// jiff's strftime has no directive for quarters, so `%q` is replaced with the quarter (1-4) of the given month
+/// before the format string is handed to jiff. `%%` is left alone so that `%%q` still formats as a literal `%q`.
+/// As with jiff's numeric directives, `%q` takes the `-`, `_` and `0` padding flags and a width (like `%_2q`), and
+/// ignores the `^` and `#` case flags.
+pub fn expand_quarter_directive(format_str: &str, month: i8) -> String {
+    let mut ret = String::with_capacity(format_str.len());
+    let mut chars = format_str.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            ret.push(c);
+            continue
+        }
+        let mut flags = String::new();
+        while let Some(flag) = chars.next_if(|next| "-_0^#".contains(*next)) {
+            flags.push(flag);
+        }
+        let mut width = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            width.push(digit);
+        }
+        match chars.next() {
+            Some('q') => {
+                let quarter = quarter_of_month(month);
+                let width = width.parse().unwrap_or(1);
+                if flags.contains('-') {
+                    ret.push_str(&quarter.to_string());
+                } else if flags.contains('_') {
+                    ret.push_str(&format!("{quarter:>width$}"));
+                } else {
+                    ret.push_str(&format!("{quarter:0>width$}"));
+                }
+            }
+            Some(next) => {
+                ret.push(c);
+                ret.push_str(&flags);
+                ret.push_str(&width);
+                ret.push(next);
+            }
+            None => {
+                ret.push(c);
+                ret.push_str(&flags);
+                ret.push_str(&width);
+            }
+        }
+    }
+    ret
+}
+
+/// Parses AutoHotkey's YYYYMMDDHH24MISS format (as in `A_Now`). As in AutoHotkey, it can be cut short after any field
+/// (`2025`, `20250310` or `202503101430`), with the missing month and day defaulting to 01 and the missing time fields
+/// to 00. The year has to be in 1601-9999, the range AutoHotkey supports.
+pub fn parse_ahk_time(s: &str) -> Result<DateTime, String> {
+    let len = s.len();
+    if ![4, 6, 8, 10, 12, 14].contains(&len) || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("invalid YYYYMMDDHH24MISS timestamp '{s}' (expected 4 to 14 digits, an even number of them)"))
+    }
+    let field = |start: usize, default: i8| if len > start { s[start..start + 2].parse().unwrap() } else { default };
+    let year: i16 = s[0..4].parse().unwrap();
+    if year < 1601 {
+        return Err(format!("the year of YYYYMMDDHH24MISS timestamp '{s}' is before 1601"))
+    }
+    DateTime::new(year, field(4, 1), field(6, 1), field(8, 0), field(10, 0), field(12, 0), 0).map_err(|e| e.to_string())
+}
+
+/// Formats a datetime as a full YYYYMMDDHH24MISS timestamp, as an integer. Fractional seconds are dropped.
+pub fn ahk_time_of(datetime: DateTime) -> Result<i64, String> {
+    if datetime.year() < 1601 {
+        return Err(format!("{datetime} is before 1601, which YYYYMMDDHH24MISS timestamps can't represent"))
+    }
+    let date = datetime.year() as i64 * 10000 + datetime.month() as i64 * 100 + datetime.day() as i64;
+    let time = datetime.hour() as i64 * 10000 + datetime.minute() as i64 * 100 + datetime.second() as i64;
+    Ok(date * 1_000_000 + time)
+}
+
+const MILLISECONDS_PER_DAY: i64 = 86_400_000;
+
+fn ole_epoch() -> DateTime {
+    jiff::civil::datetime(1899, 12, 30, 0, 0, 0, 0)
+}
+
+/// The datetime a number of days (with the time of day as the fraction) after `epoch`, to the nearest millisecond.
+fn datetime_from_days(epoch: DateTime, days: f64) -> Result<DateTime, String> {
+    let milliseconds = (days * MILLISECONDS_PER_DAY as f64).round() as i64;
+    epoch.checked_add(SignedDuration::from_millis(milliseconds)).map_err(|e| e.to_string())
+}
+
+/// The number of days (with the time of day as the fraction) from `epoch` to the datetime. Sub-millisecond precision
+/// is dropped.
+fn days_from(epoch: DateTime, datetime: DateTime) -> i64 {
+    epoch.duration_until(datetime).as_nanos().div_euclid(1_000_000) as i64
+}
+
+/// Reads an OLE Automation date: days since 1899-12-30, with the time of day as the fraction. Before 1899-12-30 the
+/// value is negative but the fraction still counts forward from midnight, so -1.25 is 1899-12-29 06:00. The result is
+/// rounded to the nearest millisecond.
+pub fn ole_date_to_datetime(value: f64) -> Result<DateTime, String> {
+    if !value.is_finite() || value <= -657435.0 || value >= 2958466.0 {
+        return Err(format!("OLE Automation date {value} is out of range (0100-01-01 to 9999-12-31)"))
+    }
+    let mut milliseconds = (value * MILLISECONDS_PER_DAY as f64).round() as i64;
+    if milliseconds < 0 {
+        // the whole days count backward but the fraction counts forward
+        milliseconds -= (milliseconds % MILLISECONDS_PER_DAY) * 2;
+    }
+    ole_epoch().checked_add(SignedDuration::from_millis(milliseconds)).map_err(|e| e.to_string())
+}
+
+/// The datetime as an OLE Automation date (see [`ole_date_to_datetime`]).
+pub fn ole_date_of(datetime: DateTime) -> Result<f64, String> {
+    if datetime.year() < 100 {
+        return Err(format!("{datetime} is before 0100-01-01, which OLE Automation dates can't represent"))
+    }
+    let mut milliseconds = days_from(ole_epoch(), datetime);
+    if milliseconds < 0 {
+        let fraction = milliseconds % MILLISECONDS_PER_DAY;
+        if fraction != 0 {
+            milliseconds -= (MILLISECONDS_PER_DAY + fraction) * 2;
+        }
+    }
+    Ok(milliseconds as f64 / MILLISECONDS_PER_DAY as f64)
+}
+
+/// Reads an Excel serial date, rounded to the nearest millisecond.
+pub fn excel_serial_to_datetime(serial: f64, system: ExcelDateSystem) -> Result<DateTime, String> {
+    if !serial.is_finite() || !(0.0..2958466.0).contains(&serial) {
+        return Err(format!("Excel serial date {serial} is out of range"))
+    }
+    match system {
+        ExcelDateSystem::Date1904 => datetime_from_days(jiff::civil::datetime(1904, 1, 1, 0, 0, 0, 0), serial),
+        // serial 0 is "1900-01-00", which is taken as 1899-12-31
+        ExcelDateSystem::Date1900 if serial < 60.0 => datetime_from_days(jiff::civil::datetime(1899, 12, 31, 0, 0, 0, 0), serial),
+        ExcelDateSystem::Date1900 if serial < 61.0 => Err(format!("Excel serial date {serial} is on 1900-02-29, which doesn't exist")),
+        ExcelDateSystem::Date1900 => datetime_from_days(ole_epoch(), serial),
+    }
+}
+
+/// The datetime as an Excel serial date. Sub-millisecond precision is dropped.
+pub fn excel_serial_of(datetime: DateTime, system: ExcelDateSystem) -> Result<f64, String> {
+    let epoch = match system {
+        ExcelDateSystem::Date1904 => jiff::civil::datetime(1904, 1, 1, 0, 0, 0, 0),
+        // the serials before March 1900 are one higher than OLE Automation dates, because of the fake February 29
+        ExcelDateSystem::Date1900 if datetime < jiff::civil::datetime(1900, 3, 1, 0, 0, 0, 0) => jiff::civil::datetime(1899, 12, 31, 0, 0, 0, 0),
+        ExcelDateSystem::Date1900 => ole_epoch(),
+    };
+    let milliseconds = days_from(epoch, datetime);
+    if milliseconds < 0 {
+        return Err(format!("{datetime} is before the start of the Excel date system"))
+    }
+    Ok(milliseconds as f64 / MILLISECONDS_PER_DAY as f64)
+}
+
+/// The length of a unit in nanoseconds, for counting time from an epoch. Days are taken as 24 hours; weeks and
+/// larger units aren't accepted.
+pub fn epoch_unit_nanoseconds(unit: Unit) -> Result<i128, String> {
+    match unit {
+        Unit::Nanosecond => Ok(1),
+        Unit::Microsecond => Ok(1_000),
+        Unit::Millisecond => Ok(1_000_000),
+        Unit::Second => Ok(1_000_000_000),
+        Unit::Minute => Ok(60_000_000_000),
+        Unit::Hour => Ok(3_600_000_000_000),
+        Unit::Day => Ok(86_400_000_000_000),
+        _ => Err(format!("unit must be days or smaller, got {unit:?}")),
+    }
+}
+
+/// 100-nanosecond ticks from 1582-10-15, the epoch of UUID versions 1 and 6, to 1970-01-01.
+const UUID_UNIX_EPOCH: i64 = 122_192_928_000_000_000;
+
+/// The time embedded in a UUID of version 1, 6 or 7. The UUID can be hyphenated or not, and wrapped in braces or
+/// prefixed with `urn:uuid:`.
+pub fn uuid_timestamp(s: &str) -> Result<Timestamp, String> {
+    let trimmed = s.trim();
+    let hex = if trimmed.get(..9).is_some_and(|prefix| prefix.eq_ignore_ascii_case("urn:uuid:")) {
+        &trimmed[9..]
+    } else if trimmed.starts_with('{') && trimmed.ends_with('}') {
+        &trimmed[1..trimmed.len() - 1]
+    } else {
+        trimmed
+    };
+    let digits: String = match hex.len() {
+        32 => hex.to_string(),
+        36 if [8, 13, 18, 23].iter().all(|&i| hex.as_bytes()[i] == b'-') => hex.replace('-', ""),
+        _ => return Err(format!("invalid UUID: {s:?}")),
+    };
+    let uuid = match u128::from_str_radix(&digits, 16) {
+        Ok(uuid) if digits.bytes().all(|b| b.is_ascii_hexdigit()) => uuid,
+        _ => return Err(format!("invalid UUID: {s:?}")),
+    };
+    if (uuid >> 62) & 0b11 != 0b10 {
+        return Err(format!("UUID {s:?} is not an RFC 9562 UUID, so it has no timestamp"))
+    }
+    let ticks = match (uuid >> 76) & 0xF {
+        1 => ((uuid >> 64) & 0x0FFF) << 48 | ((uuid >> 80) & 0xFFFF) << 32 | uuid >> 96,
+        6 => (uuid >> 96) << 28 | ((uuid >> 80) & 0xFFFF) << 12 | (uuid >> 64) & 0x0FFF,
+        7 => return Timestamp::from_millisecond((uuid >> 80) as i64).map_err(|e| e.to_string()),
+        version => return Err(format!("UUID {s:?} is version {version}, which has no timestamp")),
+    };
+    Timestamp::from_nanosecond((ticks as i128 - UUID_UNIX_EPOCH as i128) * 100).map_err(|e| e.to_string())
+}
+
+/// The time embedded in a ULID: its first 10 characters, a count of milliseconds since 1970-01-01 in Crockford's
+/// base 32.
+pub fn ulid_timestamp(s: &str) -> Result<Timestamp, String> {
+    const CROCKFORD: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+    let trimmed = s.trim();
+    if trimmed.len() != 26 {
+        return Err(format!("invalid ULID: {s:?} (a ULID has 26 characters)"))
+    }
+    let mut milliseconds: i64 = 0;
+    for (i, c) in trimmed.bytes().enumerate() {
+        let digit = match c.to_ascii_uppercase() {
+            b'O' => 0,
+            b'I' | b'L' => 1,
+            upper => match CROCKFORD.iter().position(|&d| d == upper) {
+                Some(digit) => digit as i64,
+                None => return Err(format!("invalid ULID: {s:?} ({:?} is not a base 32 digit)", c as char)),
+            }
+        };
+        if i == 0 && digit > 7 {
+            return Err(format!("invalid ULID: {s:?} (its timestamp is larger than 48 bits)"))
+        }
+        if i < 10 {
+            milliseconds = milliseconds << 5 | digit;
+        }
+    }
+    Timestamp::from_millisecond(milliseconds).map_err(|e| e.to_string())
+}
 
 #[cfg(test)]
 mod test {
@@ -135,4 +588,58 @@ mod test {
         println!("{}", *global_string().lock().unwrap());
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_expand_quarter_directive() {
+        assert_eq!(expand_quarter_directive("%Y-Q%q", 11), "%Y-Q4");
+        assert_eq!(expand_quarter_directive("%%q %q", 1), "%%q 1");
+        assert_eq!(expand_quarter_directive("100%", 6), "100%");
+        assert_eq!(expand_quarter_directive("%-q %_2q %2q %03q %^q", 4), "2  2 02 002 2");
+        assert_eq!(expand_quarter_directive("%-d %_3Y %5", 4), "%-d %_3Y %5");
+    }
+
+    #[test]
+    fn test_parse_ahk_time() {
+        assert_eq!(parse_ahk_time("20250310143005").unwrap().to_string(), "2025-03-10T14:30:05");
+        assert_eq!(parse_ahk_time("2025").unwrap().to_string(), "2025-01-01T00:00:00");
+        assert_eq!(parse_ahk_time("2025031014").unwrap().to_string(), "2025-03-10T14:00:00");
+        assert!(parse_ahk_time("202503101").is_err());
+        assert!(parse_ahk_time("20250230").is_err());
+        assert!(parse_ahk_time("15000101").is_err());
+        assert_eq!(ahk_time_of(parse_ahk_time("99991231235959").unwrap()).unwrap(), 99991231235959);
+    }
+
+    #[test]
+    fn test_ole_date_and_excel_serial() {
+        let datetime = |s: &str| s.parse::<DateTime>().unwrap();
+        assert_eq!(ole_date_to_datetime(45726.6041666667).unwrap(), datetime("2025-03-10T14:30"));
+        assert_eq!(ole_date_to_datetime(-1.25).unwrap(), datetime("1899-12-29T06:00"));
+        assert_eq!(ole_date_of(datetime("1899-12-29T06:00")).unwrap(), -1.25);
+        assert_eq!(ole_date_of(datetime("1899-12-30T18:00")).unwrap(), 0.75);
+        assert_eq!(excel_serial_of(datetime("1900-02-28"), ExcelDateSystem::Date1900).unwrap(), 59.0);
+        assert_eq!(excel_serial_of(datetime("1900-03-01"), ExcelDateSystem::Date1900).unwrap(), 61.0);
+        assert!(excel_serial_to_datetime(60.5, ExcelDateSystem::Date1900).is_err());
+        assert_eq!(excel_serial_to_datetime(1.0, ExcelDateSystem::Date1900).unwrap(), datetime("1900-01-01"));
+        assert_eq!(excel_serial_to_datetime(44264.5, ExcelDateSystem::Date1904).unwrap(), datetime("2025-03-10T12:00"));
+    }
+
+    #[test]
+    fn test_uuid_and_ulid_timestamps() {
+        // the examples from RFC 9562, all generated at 2022-02-22 14:22:22 in UTC-5
+        assert_eq!(uuid_timestamp("C232AB00-9414-11EC-B3C8-9F6BDECED846").unwrap().to_string(), "2022-02-22T19:22:22Z");
+        assert_eq!(uuid_timestamp("{1ec9414c-232a-6b00-b3c8-9f6bdeced846}").unwrap().to_string(), "2022-02-22T19:22:22Z");
+        assert_eq!(uuid_timestamp("urn:uuid:017f22e2-79b0-7cc3-98c4-dc0c0c07398f").unwrap().to_string(), "2022-02-22T19:22:22Z");
+        assert!(uuid_timestamp("919108f7-52d1-4320-9bac-f847db4148a8").is_err());
+        assert!(uuid_timestamp("C232AB00-9414-11EC-B3C8-9F6BDECED84").is_err());
+        assert!(uuid_timestamp("C232AB00-9414-11EC-B3C8+9F6BDECED846").is_err());
+        assert!(uuid_timestamp("ééééé-not-a-uuid").is_err());
+        assert!(uuid_timestamp("urn:uuid:éééééééééééééééé").is_err());
+        assert!(ulid_timestamp("01ARZ3NDEKTSV4RRFFQ69G5é").is_err());
+        assert!("RRULEé:FREQ=DAILY".parse::<crate::rrule::RRule>().is_err());
+        assert!("FREQ=WEEKLY;BYDAY=éA".parse::<crate::rrule::RRule>().is_err());
+        assert_eq!(ulid_timestamp("01ARZ3NDEKTSV4RRFFQ69G5FAV").unwrap().to_string(), "2016-07-30T23:54:10.259Z");
+        assert_eq!(ulid_timestamp("01arz3ndektsv4rrffq69g5fav").unwrap().to_string(), "2016-07-30T23:54:10.259Z");
+        assert!(ulid_timestamp("81ARZ3NDEKTSV4RRFFQ69G5FAV").is_err());
+        assert!(ulid_timestamp("01ARZ3NDEKTSV4RRFFQ69G5FAU").is_err());
+    }
+}
@@ -0,0 +1,222 @@
+use std::ffi::{c_char, c_longlong};
+use jiff::civil::{Date, Time, Weekday};
+use jiff::tz::TimeZone;
+use jiff::{SignedDuration, Timestamp};
+use crate::business::TempusBusinessCalendar;
+use crate::duration::TempusSignedDuration;
+use crate::range::{normalize, HalfOpen};
+use crate::time::TempusTime;
+use crate::tz::TempusTimeZone;
+use crate::utils::set_last_error_message;
+use crate::zoned::TempusZoned;
+
+/// Working hours in a time zone, for measuring and adding working time (SLA timers and the like). Each weekday has
+/// its own shifts, and a business calendar can take out holidays.
+#[repr(C)]
+pub struct TempusWorkingHours {
+    tz: TimeZone,
+    /// Indexed by the weekday's offset from Sunday (Sunday is 0). A shift that ends at or before the time it starts
+    /// runs past midnight into the next day.
+    shifts: [Vec<(Time, Time)>; 7],
+    calendar: Option<TempusBusinessCalendar>,
+}
+
+impl TempusWorkingHours {
+    fn is_working_date(&self, date: Date) -> bool {
+        match &self.calendar {
+            Some(calendar) => calendar.is_business_day(date),
+            None => true
+        }
+    }
+
+    /// The working time of the shifts that start on the given local date.
+    fn windows_of(&self, date: Date) -> Result<Vec<HalfOpen<Timestamp>>, String> {
+        if !self.is_working_date(date) {
+            return Ok(Vec::new())
+        }
+        let mut windows = Vec::new();
+        for (start, end) in &self.shifts[date.weekday().to_sunday_zero_offset() as usize] {
+            let end_date = if end <= start { date.tomorrow().map_err(|e| e.to_string())? } else { date };
+            let start = date.to_datetime(*start).to_zoned(self.tz.clone()).map_err(|e| e.to_string())?;
+            let end = end_date.to_datetime(*end).to_zoned(self.tz.clone()).map_err(|e| e.to_string())?;
+            windows.push(HalfOpen{start: start.timestamp(), end: end.timestamp()});
+        }
+        Ok(windows)
+    }
+
+    /// The working time within `range`, as a normalized set of ranges.
+    fn working_windows(&self, range: HalfOpen<Timestamp>) -> Result<Vec<HalfOpen<Timestamp>>, String> {
+        let first = range.start.to_zoned(self.tz.clone()).date();
+        // a shift that started the day before can still be running at the start of the range
+        let mut date = first.yesterday().unwrap_or(first);
+        let last = range.end.to_zoned(self.tz.clone()).date();
+        let mut windows = Vec::new();
+        while date <= last {
+            windows.extend(self.windows_of(date)?.iter().filter_map(|window| window.intersection(&range)));
+            date = match date.tomorrow() {
+                Ok(next) => next,
+                Err(_) => break,
+            };
+        }
+        Ok(normalize(windows))
+    }
+
+    /// The working time from `start` until `end`. Negative if `end` is before `start`.
+    pub fn duration_between(&self, start: Timestamp, end: Timestamp) -> Result<SignedDuration, String> {
+        let (range, negate) = if start <= end {
+            (HalfOpen{start, end}, false)
+        } else {
+            (HalfOpen{start: end, end: start}, true)
+        };
+        let mut total = SignedDuration::ZERO;
+        for window in self.working_windows(range)? {
+            total = total.checked_add(window.start.duration_until(window.end)).ok_or("working time overflowed")?;
+        }
+        Ok(if negate { -total } else { total })
+    }
+
+    /// Moves forward (or backward, for a negative duration) by the given amount of working time. When the amount
+    /// runs out exactly at the end of a shift, the result is the end of that shift rather than the start of the next.
+    pub fn add(&self, start: Timestamp, duration: SignedDuration) -> Result<Timestamp, String> {
+        if duration.is_zero() {
+            return Ok(start)
+        }
+        if self.shifts.iter().all(|shifts| shifts.is_empty()) {
+            return Err("there are no working hours to add".to_string())
+        }
+        let forward = duration.is_positive();
+        let mut remaining = duration.abs();
+        let mut current = start;
+        let mut date = start.to_zoned(self.tz.clone()).date();
+        date = if forward { date.yesterday().unwrap_or(date) } else { date.tomorrow().unwrap_or(date) };
+        // guards against shifts that only fall on days the calendar takes out
+        let mut days_without_work = 0;
+        loop {
+            if days_without_work > 3660 {
+                return Err("there are no working hours within ten years".to_string())
+            }
+            let mut windows = Vec::new();
+            // the neighbouring days are included so that overlapping shifts are merged before they're counted
+            for day in [date.yesterday(), Ok(date), date.tomorrow()].into_iter().flatten() {
+                windows.extend(self.windows_of(day)?);
+            }
+            let mut windows = normalize(windows);
+            if windows.is_empty() {
+                days_without_work += 1;
+            } else {
+                days_without_work = 0;
+            }
+            if !forward {
+                windows.reverse();
+            }
+            for window in windows {
+                let available = if forward {
+                    if window.end <= current {
+                        continue
+                    }
+                    let from = current.max(window.start);
+                    current = from;
+                    from.duration_until(window.end)
+                } else {
+                    if window.start >= current {
+                        continue
+                    }
+                    let from = current.min(window.end);
+                    current = from;
+                    window.start.duration_until(from)
+                };
+                if remaining <= available {
+                    let signed = if forward { remaining } else { -remaining };
+                    return current.checked_add(signed).map_err(|e| e.to_string())
+                }
+                remaining -= available;
+                current = if forward { window.end } else { window.start };
+            }
+            date = if forward { date.tomorrow() } else { date.yesterday() }.map_err(|e| e.to_string())?;
+        }
+    }
+
+    pub fn is_working_time(&self, timestamp: Timestamp) -> Result<bool, String> {
+        let range = HalfOpen{start: timestamp, end: timestamp.checked_add(SignedDuration::from_nanos(1)).map_err(|e| e.to_string())?};
+        Ok(!self.working_windows(range)?.is_empty())
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn working_hours_new(ttz: &TempusTimeZone) -> Box<TempusWorkingHours> {
+    Box::new(TempusWorkingHours{tz: ttz.tz.clone(), shifts: Default::default(), calendar: None})
+}
+
+/// Adds a shift from `start` until `end` on the given weekday. A shift that ends at or before the time it starts
+/// runs past midnight into the next day.
+#[no_mangle]
+pub extern "C" fn working_hours_add_shift(twh: &mut TempusWorkingHours, weekday_i: i8, start: &TempusTime, end: &TempusTime) -> c_longlong {
+    let weekday = match Weekday::from_sunday_one_offset(weekday_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -1
+        }
+        Ok(weekday) => weekday
+    };
+    twh.shifts[weekday.to_sunday_zero_offset() as usize].push((start.time, end.time));
+    0
+}
+
+/// Uses a copy of the business calendar to take holidays (and the calendar's weekend days) out of the working hours.
+#[no_mangle]
+pub extern "C" fn working_hours_set_calendar(twh: &mut TempusWorkingHours, tcal: &TempusBusinessCalendar) -> c_longlong {
+    twh.calendar = Some(tcal.clone());
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn working_hours_duration_between(twh: &TempusWorkingHours, start: &TempusZoned, end: &TempusZoned, out_duration: *mut *mut TempusSignedDuration) -> c_longlong {
+    match twh.duration_between(start.zoned.timestamp(), end.zoned.timestamp()) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(duration) => {
+            let tsd = TempusSignedDuration{duration};
+            tsd.stuff_into(out_duration);
+            0
+        }
+    }
+}
+
+/// Adds working time to a zoned datetime. The result is in the zoned datetime's time zone.
+#[no_mangle]
+pub extern "C" fn working_hours_add(twh: &TempusWorkingHours, tzoned: &TempusZoned, tsd: &TempusSignedDuration, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    match twh.add(tzoned.zoned.timestamp(), tsd.duration) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(timestamp) => {
+            let new_zoned = TempusZoned{zoned: timestamp.to_zoned(tzoned.zoned.time_zone().clone())};
+            new_zoned.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+/// 1 if the zoned datetime is within working hours, 0 if not, or -1 on error.
+#[no_mangle]
+pub extern "C" fn working_hours_is_working_time(twh: &TempusWorkingHours, tzoned: &TempusZoned) -> c_char {
+    match twh.is_working_time(tzoned.zoned.timestamp()) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(working) => working as i8
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn free_working_hours(twh: Box<TempusWorkingHours>) -> c_longlong {
+    let raw = Box::into_raw(twh);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
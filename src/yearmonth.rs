@@ -0,0 +1,214 @@
+use std::cmp::Ordering;
+use std::ffi::{c_char, c_longlong, c_short};
+use std::str::FromStr;
+use jiff::civil::Date;
+use jiff::{Span, Unit};
+use crate::date::TempusDate;
+use crate::utils::{ahk_str_to_string, set_last_error_message, string_into_ahk_buff, AHKStringBuffer, AHKWstr};
+
+/// A month of a particular year, like Temporal's `PlainYearMonth`. Stored as the first day of the month.
+#[repr(C)]
+pub struct TempusYearMonth {
+    pub first: Date
+}
+
+impl TempusYearMonth {
+    pub fn stuff_into(self, pointer: *mut *mut TempusYearMonth) {
+        let handle = Box::new(self);
+        unsafe {
+            *pointer = Box::into_raw(handle);
+        }
+    }
+}
+
+impl FromStr for TempusYearMonth {
+    type Err = String;
+
+    /// Accepts `YYYY-MM` (or `±YYYYYY-MM` for years outside 0-9999). A full date is also accepted and its day is
+    /// dropped.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((year_str, month_str)) = s.rsplit_once('-') {
+            let digits = year_str.trim_start_matches(['+', '-']);
+            let year_ok = match year_str.len() - digits.len() {
+                0 => digits.len() == 4,
+                1 => digits.len() == 6,
+                _ => false,
+            };
+            if year_ok && month_str.len() == 2 && digits.bytes().chain(month_str.bytes()).all(|b| b.is_ascii_digit()) {
+                let year: i16 = year_str.parse().map_err(|_| format!("year in '{s}' is not in the required range"))?;
+                let month: i8 = month_str.parse().map_err(|_| format!("invalid month in '{s}'"))?;
+                let first = Date::new(year, month, 1).map_err(|e| e.to_string())?;
+                return Ok(TempusYearMonth{first})
+            }
+        }
+        match s.parse::<Date>() {
+            Ok(date) => Ok(TempusYearMonth{first: date.first_of_month()}),
+            Err(_) => Err(format!("failed to parse '{s}' as a year-month (expected a string like 2025-03)"))
+        }
+    }
+}
+
+impl std::fmt::Display for TempusYearMonth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // jiff formats the year the same way we want, so drop the `-01` day from the date
+        let date = self.first.to_string();
+        write!(f, "{}", &date[..date.len() - 3])
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_new(year: i16, month: i8, out_yearmonth: *mut *mut TempusYearMonth) -> c_longlong {
+    match Date::new(year, month, 1) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(first) => {
+            let tym = TempusYearMonth{first};
+            tym.stuff_into(out_yearmonth);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_parse(ahk_string: AHKWstr, out_yearmonth: *mut *mut TempusYearMonth) -> c_longlong {
+    match ahk_str_to_string(ahk_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(s) => {
+            match TempusYearMonth::from_str(&s) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(tym) => {
+                    tym.stuff_into(out_yearmonth);
+                    0
+                }
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_from_date(tdate: &TempusDate) -> Box<TempusYearMonth> {
+    Box::new(TempusYearMonth{first: tdate.date.first_of_month()})
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_string_length(tym: &TempusYearMonth) -> usize {
+    tym.to_string().len()
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_to_string(tym: &TempusYearMonth, out_buff: AHKStringBuffer, buff_len: usize) -> c_longlong {
+    string_into_ahk_buff(tym.to_string(), out_buff, buff_len);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_compare(tym: &TempusYearMonth, other: &TempusYearMonth) -> c_char {
+    match tym.first.cmp(&other.first) {
+        Ordering::Less => {-1}
+        Ordering::Equal => {0}
+        Ordering::Greater => {1}
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_year(tym: &TempusYearMonth) -> c_short {
+    tym.first.year()
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_month(tym: &TempusYearMonth) -> c_char {
+    tym.first.month()
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_days_in_month(tym: &TempusYearMonth) -> c_char {
+    tym.first.days_in_month()
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_days_in_year(tym: &TempusYearMonth) -> c_short {
+    tym.first.days_in_year()
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_in_leap_year(tym: &TempusYearMonth) -> c_char {
+    tym.first.in_leap_year() as i8
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_checked_add_months(tym: &TempusYearMonth, months: i64, out_yearmonth: *mut *mut TempusYearMonth) -> c_longlong {
+    let span = match Span::new().try_months(months) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -1
+        }
+        Ok(span) => span
+    };
+    match tym.first.checked_add(span) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(first) => {
+            let new_tym = TempusYearMonth{first};
+            new_tym.stuff_into(out_yearmonth);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_months_until(tym: &TempusYearMonth, other: &TempusYearMonth, out_months: &mut i64) -> c_longlong {
+    match tym.first.until((Unit::Month, other.first)) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(span) => {
+            *out_months = span.get_months() as i64;
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_to_date(tym: &TempusYearMonth, day: i8, out_date: *mut *mut TempusDate) -> c_longlong {
+    match tym.first.with().day(day).build() {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(date) => {
+            let td = TempusDate{date};
+            td.stuff_into(out_date);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_first_of_month(tym: &TempusYearMonth) -> Box<TempusDate> {
+    Box::new(TempusDate{date: tym.first})
+}
+
+#[no_mangle]
+pub extern "C" fn yearmonth_last_of_month(tym: &TempusYearMonth) -> Box<TempusDate> {
+    Box::new(TempusDate{date: tym.first.last_of_month()})
+}
+
+#[no_mangle]
+pub extern "C" fn free_yearmonth(tym: Box<TempusYearMonth>) -> c_longlong {
+    let raw = Box::into_raw(tym);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
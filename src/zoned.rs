@@ -2,18 +2,18 @@ use std::cmp::Ordering;
 use std::ffi::c_longlong;
 use std::ffi::{c_char, c_int, c_short};
 use std::str::FromStr;
-use jiff::{Error, Zoned, ZonedDifference, ZonedRound};
+use jiff::{Error, RoundMode, Span, Unit, Zoned, ZonedDifference, ZonedRound, ZonedWith};
 use jiff::civil::{Era, Weekday};
 use jiff::fmt::strtime::BrokenDownTime;
-use crate::date::TempusDate;
-use crate::datetime::TempusDateTime;
+use crate::date::{date_end_of_period, week_of_month_of_date, week_of_year_of_date, date_start_of_period, TempusDate};
+use crate::datetime::{datetime_start_of_period, TempusDateTime};
 use crate::duration::TempusSignedDuration;
 use crate::isoweekdate::TempusISOWeekDate;
 use crate::span::TempusSpan;
 use crate::time::TempusTime;
 use crate::timestamp::TempusTimestamp;
 use crate::tz::TempusTimeZone;
-use crate::utils::{ahk_str_to_string, AHKWstr, set_last_error_message, AHKStringBuffer, string_into_ahk_buff, unit_from_i8, round_mode_from_i8};
+use crate::utils::{expand_quarter_directive, first_week_from_i8, half_year_of_month, quarter_of_month, quarters_to_span, ahk_str_to_string, AHKWstr, set_last_error_message, AHKStringBuffer, string_into_ahk_buff, unit_from_i8, round_mode_from_i8, era_from_i8, disambiguation_from_i8, period_from_i8, ahk_time_of, parse_ahk_time, Period};
 
 #[repr(C)]
 pub struct TempusZoned {
@@ -33,6 +33,11 @@ impl TempusZoned {
     }
 }
 
+#[repr(C)]
+pub struct TempusZonedWith {
+    with: ZonedWith
+}
+
 impl FromStr for TempusZoned {
     type Err = Error;
 
@@ -111,6 +116,45 @@ pub extern "C" fn zoned_parse(ahk_zone_str: AHKWstr, out_zoned: *mut *mut Tempus
     }
 }
 
+/// Parses AutoHotkey's YYYYMMDDHH24MISS format (like `A_Now`) as a local time in the given time zone. A local time
+/// that falls in a gap is moved forward and one that happens twice is the earlier one.
+#[no_mangle]
+pub extern "C" fn zoned_from_ahk_time(ahk_time_string: AHKWstr, ttz: &TempusTimeZone, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    match ahk_str_to_string(ahk_time_string) {
+        Err(_) => {
+            set_last_error_message("could not read ahk string".to_string());
+            -1
+        }
+        Ok(time_string) => {
+            match parse_ahk_time(&time_string).and_then(|datetime| datetime.to_zoned(ttz.tz.clone()).map_err(|e| e.to_string())).map(|zoned| TempusZoned{zoned}) {
+                Err(e) => {
+                    set_last_error_message(e);
+                    -2
+                }
+                Ok(value) => {
+                    value.stuff_into(out_zoned);
+                    0
+                }
+            }
+        }
+    }
+}
+
+/// The local time of the zoned datetime as a YYYYMMDDHH24MISS timestamp. Fractional seconds are dropped.
+#[no_mangle]
+pub extern "C" fn zoned_to_ahk_time(tzoned: &TempusZoned, out_ahk_time: &mut i64) -> c_longlong {
+    match ahk_time_of(tzoned.zoned.datetime()) {
+        Err(e) => {
+            set_last_error_message(e);
+            -1
+        }
+        Ok(ahk_time) => {
+            *out_ahk_time = ahk_time;
+            0
+        }
+    }
+}
+
 
 #[no_mangle]
 pub extern "C" fn zoned_hour(tzoned: &TempusZoned) -> c_char {
@@ -177,6 +221,7 @@ pub extern "C" fn zoned_strftime_length(tzoned: &TempusZoned, ahk_format_str: AH
             -1
         }
         Ok(format_str) => {
+            let format_str = expand_quarter_directive(&format_str, tzoned.zoned.month());
             let bdt = BrokenDownTime::from(&tzoned.zoned);
             let mut buf = String::new();
             match bdt.format(format_str, &mut buf) {
@@ -210,6 +255,7 @@ pub extern "C" fn zoned_strftime(tzoned: &TempusZoned, ahk_format_str: AHKWstr,
             -1
         }
         Ok(format_str) => {
+            let format_str = expand_quarter_directive(&format_str, tzoned.zoned.month());
             let bdt = BrokenDownTime::from(&tzoned.zoned);
             let mut buf = String::new();
             match bdt.format(format_str, &mut buf) {
@@ -373,6 +419,96 @@ pub extern "C" fn zoned_last_of_year(tzoned: &TempusZoned, out_zoned: *mut *mut
     }
 }
 
+#[no_mangle]
+pub extern "C" fn zoned_quarter(tzoned: &TempusZoned) -> c_char {
+    quarter_of_month(tzoned.zoned.month())
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_half_year(tzoned: &TempusZoned) -> c_char {
+    half_year_of_month(tzoned.zoned.month())
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_first_of_quarter(tzoned: &TempusZoned, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    let date = match date_start_of_period(tzoned.zoned.date(), Period::Quarter, Weekday::Monday) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(date) => date
+    };
+    match tzoned.zoned.with().date(date).build() {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(zoned) => {
+            let new_zoned = TempusZoned{zoned};
+            new_zoned.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_last_of_quarter(tzoned: &TempusZoned, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    let date = match date_end_of_period(tzoned.zoned.date(), Period::Quarter, Weekday::Monday) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(date) => date
+    };
+    match tzoned.zoned.with().date(date).build() {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(zoned) => {
+            let new_zoned = TempusZoned{zoned};
+            new_zoned.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_checked_add_quarters(tzoned: &TempusZoned, quarters: i64, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    let span = match quarters_to_span(quarters) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(span) => span
+    };
+    match tzoned.zoned.checked_add(span) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -2
+        }
+        Ok(zoned) => {
+            let new_zoned = TempusZoned{zoned};
+            new_zoned.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_quarters_until(tzoned: &TempusZoned, other: &TempusZoned, out_quarters: &mut i64) -> c_longlong {
+    match tzoned.zoned.until((Unit::Month, &other.zoned)) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(span) => {
+            *out_quarters = span.get_months() as i64 / 3;
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn zoned_days_in_month(tzoned: &TempusZoned) -> c_char {
     tzoned.zoned.days_in_month()
@@ -440,6 +576,42 @@ pub extern "C" fn zoned_nth_weekday_of_month(tzoned: &TempusZoned, nth: i8, week
     }
 }
 
+#[no_mangle]
+pub extern "C" fn zoned_days_until_weekday(tzoned: &TempusZoned, weekday_i: i8) -> c_char {
+    match Weekday::from_sunday_one_offset(weekday_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(weekday) => {
+            tzoned.zoned.weekday().until(weekday)
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_days_since_weekday(tzoned: &TempusZoned, weekday_i: i8) -> c_char {
+    match Weekday::from_sunday_one_offset(weekday_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(weekday) => {
+            tzoned.zoned.weekday().since(weekday)
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_weekday_of_month_ordinal(tzoned: &TempusZoned) -> c_char {
+    (tzoned.zoned.day() - 1) / 7 + 1
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_is_last_weekday_of_month(tzoned: &TempusZoned) -> c_char {
+    (tzoned.zoned.day() + 7 > tzoned.zoned.days_in_month()) as i8
+}
+
 #[no_mangle]
 pub extern "C" fn zoned_nth_weekday(tzoned: &TempusZoned, nth: i32, weekday_i: i8, out_datetime: *mut *mut TempusZoned) -> c_longlong {
     let weekday = match Weekday::from_sunday_one_offset(weekday_i) {
@@ -487,6 +659,63 @@ pub extern "C" fn zoned_to_isoweekdate(tzoned: &TempusZoned) -> Box<TempusISOWee
     Box::new(TempusISOWeekDate{weekdate: tzoned.zoned.clone().iso_week_date()})
 }
 
+#[no_mangle]
+pub extern "C" fn zoned_week_of_year(tzoned: &TempusZoned, week_start_i: i8, first_week_i: i8, out_year: &mut i16, out_week: &mut i8) -> c_longlong {
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -1
+        }
+        Ok(weekday) => weekday
+    };
+    let first_week_days = match first_week_from_i8(first_week_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -2
+        }
+        Ok(days) => days
+    };
+    match week_of_year_of_date(tzoned.zoned.date(), week_start, first_week_days) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok((year, week)) => {
+            *out_year = year;
+            *out_week = week;
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_week_of_month(tzoned: &TempusZoned, week_start_i: i8, first_week_i: i8, out_week: &mut i8) -> c_longlong {
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -1
+        }
+        Ok(weekday) => weekday
+    };
+    let first_week_days = match first_week_from_i8(first_week_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -2
+        }
+        Ok(days) => days
+    };
+    match week_of_month_of_date(tzoned.zoned.date(), week_start, first_week_days) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(week) => {
+            *out_week = week;
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn zoned_checked_add_span(tzoned: &TempusZoned, other: &TempusSpan, out_datetime: *mut *mut TempusZoned) -> c_longlong {
     match tzoned.zoned.checked_add(other.span) {
@@ -728,6 +957,244 @@ pub extern "C" fn zoned_compare(tzoned: &TempusZoned, other: &TempusZoned) -> c_
     }
 }
 
+/// Returns the first instant of the period containing the given zoned datetime. Periods of a day
+/// or longer start at the first instant of their first day, which is not midnight on days where
+/// midnight does not exist.
+pub(crate) fn zoned_start_of_period(zoned: &Zoned, period: Period, week_start: Weekday) -> Result<Zoned, String> {
+    match period {
+        Period::Unit(unit) if period.is_time() => {
+            let rounder = ZonedRound::new().smallest(unit).mode(RoundMode::Trunc);
+            zoned.round(rounder).map_err(|e| e.to_string())
+        }
+        _ => {
+            let start = datetime_start_of_period(zoned.datetime(), period, week_start)?;
+            start.date().to_zoned(zoned.time_zone().clone()).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Returns the last nanosecond of the period containing the given zoned datetime.
+pub(crate) fn zoned_end_of_period(zoned: &Zoned, period: Period, week_start: Weekday) -> Result<Zoned, String> {
+    let start = zoned_start_of_period(zoned, period, week_start)?;
+    let next = if period.is_time() {
+        start.checked_add(period.span()).map_err(|e| e.to_string())?
+    } else {
+        let next_date = start.date().checked_add(period.span()).map_err(|e| e.to_string())?;
+        next_date.to_zoned(zoned.time_zone().clone()).map_err(|e| e.to_string())?
+    };
+    next.checked_sub(Span::new().nanoseconds(1)).map_err(|e| e.to_string())
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_truncate(tzoned: &TempusZoned, unit_i: i8, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    let unit = match unit_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(unit) => unit
+    };
+    match zoned_start_of_period(&tzoned.zoned, Period::Unit(unit), Weekday::Monday) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(zoned) => {
+            let new_tzoned = TempusZoned{zoned};
+            new_tzoned.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_start_of(tzoned: &TempusZoned, unit_i: i8, week_start_i: i8, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    let period = match period_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(period) => period
+    };
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -2
+        }
+        Ok(weekday) => weekday
+    };
+    match zoned_start_of_period(&tzoned.zoned, period, week_start) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(zoned) => {
+            let new_tzoned = TempusZoned{zoned};
+            new_tzoned.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_end_of(tzoned: &TempusZoned, unit_i: i8, week_start_i: i8, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    let period = match period_from_i8(unit_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(period) => period
+    };
+    let week_start = match Weekday::from_sunday_one_offset(week_start_i) {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            return -2
+        }
+        Ok(weekday) => weekday
+    };
+    match zoned_end_of_period(&tzoned.zoned, period, week_start) {
+        Err(e) => {
+            set_last_error_message(e);
+            -3
+        }
+        Ok(zoned) => {
+            let new_tzoned = TempusZoned{zoned};
+            new_tzoned.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with(tzoned: &TempusZoned) -> Box<TempusZonedWith> {
+    Box::new(TempusZonedWith{with: tzoned.zoned.with()})
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_date(tzw: &mut TempusZonedWith, td: &TempusDate) -> c_longlong {
+    tzw.with = tzw.with.clone().date(td.date);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_time(tzw: &mut TempusZonedWith, tt: &TempusTime) -> c_longlong {
+    tzw.with = tzw.with.clone().time(tt.time);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_year(tzw: &mut TempusZonedWith, year: i16) -> c_longlong {
+    tzw.with = tzw.with.clone().year(year);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_era_year(tzw: &mut TempusZonedWith, year: i16, era_i: i8) -> c_longlong {
+    let era = match era_from_i8(era_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(era) => era
+    };
+    tzw.with = tzw.with.clone().era_year(year, era);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_month(tzw: &mut TempusZonedWith, month: i8) -> c_longlong {
+    tzw.with = tzw.with.clone().month(month);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_day(tzw: &mut TempusZonedWith, day: i8) -> c_longlong {
+    tzw.with = tzw.with.clone().day(day);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_day_of_year(tzw: &mut TempusZonedWith, day: i16) -> c_longlong {
+    tzw.with = tzw.with.clone().day_of_year(day);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_day_of_year_no_leap(tzw: &mut TempusZonedWith, day: i16) -> c_longlong {
+    tzw.with = tzw.with.clone().day_of_year_no_leap(day);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_hour(tzw: &mut TempusZonedWith, hour: i8) -> c_longlong {
+    tzw.with = tzw.with.clone().hour(hour);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_minute(tzw: &mut TempusZonedWith, minute: i8) -> c_longlong {
+    tzw.with = tzw.with.clone().minute(minute);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_second(tzw: &mut TempusZonedWith, second: i8) -> c_longlong {
+    tzw.with = tzw.with.clone().second(second);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_millisecond(tzw: &mut TempusZonedWith, millisecond: i16) -> c_longlong {
+    tzw.with = tzw.with.clone().millisecond(millisecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_microsecond(tzw: &mut TempusZonedWith, microsecond: i16) -> c_longlong {
+    tzw.with = tzw.with.clone().microsecond(microsecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_nanosecond(tzw: &mut TempusZonedWith, nanosecond: i16) -> c_longlong {
+    tzw.with = tzw.with.clone().nanosecond(nanosecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_subsec_nanosecond(tzw: &mut TempusZonedWith, subsec_nanosecond: i32) -> c_longlong {
+    tzw.with = tzw.with.clone().subsec_nanosecond(subsec_nanosecond);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_disambiguation(tzw: &mut TempusZonedWith, disambiguation_i: i8) -> c_longlong {
+    let disambiguation = match disambiguation_from_i8(disambiguation_i) {
+        Err(e) => {
+            set_last_error_message(e);
+            return -1
+        }
+        Ok(disambiguation) => disambiguation
+    };
+    tzw.with = tzw.with.clone().disambiguation(disambiguation);
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn zoned_with_build(tzw: &TempusZonedWith, out_zoned: *mut *mut TempusZoned) -> c_longlong {
+    match tzw.with.clone().build() {
+        Err(e) => {
+            set_last_error_message(e.to_string());
+            -1
+        }
+        Ok(zoned) => {
+            let new_zoned = TempusZoned{zoned};
+            new_zoned.stuff_into(out_zoned);
+            0
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn free_zoned(tzoned: Box<TempusZoned>) -> c_longlong {
     let raw = Box::into_raw(tzoned);
@@ -737,6 +1204,15 @@ pub extern "C" fn free_zoned(tzoned: Box<TempusZoned>) -> c_longlong {
     0
 }
 
+#[no_mangle]
+pub extern "C" fn free_zoned_with(tzw: Box<TempusZonedWith>) -> c_longlong {
+    let raw = Box::into_raw(tzw);
+    unsafe {
+        drop(Box::from_raw(raw))
+    }
+    0
+}
+
 
 
 #[cfg(test)]